@@ -0,0 +1,449 @@
+//! Strategies the computer plays the game with, to watch or to take over from the player.
+
+use std::collections::VecDeque;
+
+use crate::cell::*;
+use crate::point::*;
+use crate::topology::*;
+use crate::Game;
+
+
+/// Way of playing the game, choosing where the snake goes next.
+pub trait Strategy {
+    /// name it goes by, one of `BOTS`.
+    fn name(&self) -> &'static str;
+
+    /// direction to go in on the next tick.  asked before every tick, with no turns queued.
+    fn next_move(&mut self, game: &Game) -> Direction;
+}
+
+/// names of the strategies, for `bot`.
+pub const BOTS: [&str; 3] = ["greedy", "safe", "cycle"];
+
+/// one of `BOTS`, by name.
+pub fn bot(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        "greedy" => Some(Box::new(GreedyBot)),
+        "safe" => Some(Box::new(SafeBot)),
+        "cycle" => Some(Box::new(CycleBot::new())),
+        _ => None,
+    }
+}
+
+/// Goes the shortest way to the nearest food, whatever comes after.
+/// with no way to any food, it goes where there is the most room.
+pub struct GreedyBot;
+
+impl Strategy for GreedyBot {
+    fn name(&self) -> &'static str { "greedy" }
+
+    fn next_move(&mut self, game: &Game) -> Direction {
+        let board = Board::new(game);
+        let found = board.explore();
+        match board.nearest_food(&found).and_then(|food| board.path(&found, food)) {
+            Some(path) => path[0],
+            None => board.roomiest(),
+        }
+    }
+}
+
+/// Goes the shortest way to the nearest food only if the snake can still get to its tail
+/// once there, so it never shuts itself in.  otherwise it follows its tail the long way round,
+/// until the way to the food is safe.
+pub struct SafeBot;
+
+impl Strategy for SafeBot {
+    fn name(&self) -> &'static str { "safe" }
+
+    fn next_move(&mut self, game: &Game) -> Direction {
+        let board = Board::new(game);
+        let found = board.explore();
+        if let Some(path) = board.nearest_food(&found).and_then(|food| board.path(&found, food)) {
+            let mut after = board.clone();
+            if path.iter().all(|&dir| after.advance(dir)) && after.can_reach_tail() {
+                return path[0];
+            }
+        }
+        board
+            .moves()
+            .filter_map(|dir| {
+                let mut after = board.clone();
+                if !after.advance(dir) {
+                    return None;
+                }
+                let (moves, _) = after.explore()[after.index(after.tail())]?;
+                Some((moves, dir))
+            })
+            .max_by_key(|&(moves, _)| moves)
+            .map_or_else(|| board.roomiest(), |(_, dir)| dir)
+    }
+}
+
+/// Follows a Hamiltonian cycle, a round trip through every cell of the field, so it fills
+/// the whole of it in the end, however long it takes.
+///
+/// it plays like `SafeBot` until the snake can get onto the cycle, and on fields with
+/// no such cycle: those with walls or rocks in them, and walled ones with odd width and height.
+pub struct CycleBot {
+    /// size and topology of the field the cycle was made for.
+    field: ((usize, usize), Topology),
+    /// way to go from each cell, by row.
+    cycle: Option<Vec<Direction>>,
+}
+
+impl CycleBot {
+    pub fn new() -> Self {
+        CycleBot { field: ((0, 0), Topology::Wrapping), cycle: None }
+    }
+}
+
+impl Default for CycleBot {
+    fn default() -> Self {
+        CycleBot::new()
+    }
+}
+
+impl Strategy for CycleBot {
+    fn name(&self) -> &'static str { "cycle" }
+
+    fn next_move(&mut self, game: &Game) -> Direction {
+        let board = Board::new(game);
+        if self.field != (board.size, board.topology) {
+            self.field = (board.size, board.topology);
+            self.cycle = hamiltonian_cycle(board.size, board.topology);
+        }
+        match self.cycle {
+            Some(ref cycle) if !board.obstacles.contains(&true)
+                && (board.is_on_cycle(cycle) || board.can_get_on_cycle(cycle)) => cycle[board.index(board.head())],
+            _ => SafeBot.next_move(game),
+        }
+    }
+}
+
+const DIRECTIONS: [Direction; 4] = [Up, Down, Left, Right];
+
+/// The game as bots see it, where moves can be tried out.
+#[derive(Clone)]
+struct Board {
+    size: (usize, usize),
+    topology: Topology,
+    /// cells the snake can never go into, by row.
+    obstacles: Vec<bool>,
+    food: Vec<bool>,
+    /// the snake, head first.
+    snake: VecDeque<Point<isize>>,
+    direction: Direction,
+    /// segments the snake is yet to grow.
+    growth: usize,
+    /// segments each food grows the snake by.
+    food_growth: usize,
+}
+
+impl Board {
+    fn new(game: &Game) -> Self {
+        let field = game.field();
+        let cells: Vec<Cell> = field.rows().iter().flatten().copied().collect();
+        Board {
+            size: field.size(),
+            topology: game.config().topology,
+            obstacles: cells.iter().map(Cell::is_obstacle).collect(),
+            food: cells.iter().map(|&cell| cell == Food).collect(),
+            snake: game.segments().into_iter().map(|(p, _)| p).collect(),
+            direction: game.heading(),
+            growth: game.pending_growth(),
+            food_growth: game.config().growth,
+        }
+    }
+
+    fn index(&self, p: Point<isize>) -> usize {
+        p.y as usize * self.size.0 + p.x as usize
+    }
+
+    fn point(&self, index: usize) -> Point<isize> {
+        Point { x: (index % self.size.0) as isize, y: (index / self.size.0) as isize }
+    }
+
+    fn head(&self) -> Point<isize> { self.snake[0] }
+
+    fn tail(&self) -> Point<isize> { *self.snake.back().unwrap() }
+
+    /// directions the snake can go in, which are all but back.
+    fn moves(&self) -> impl Iterator<Item = Direction> + '_ {
+        DIRECTIONS.iter().copied().filter(move |&dir| dir != self.direction.opposite())
+    }
+
+    /// number of moves after which each cell can be gone into: never for obstacles,
+    /// and for the body once the tail has moved past it.
+    fn free_after(&self) -> Vec<usize> {
+        let mut free_after: Vec<usize> = self.obstacles
+            .iter()
+            .map(|&obstacle| if obstacle { usize::MAX } else { 0 })
+            .collect();
+        for (i, &p) in self.snake.iter().rev().enumerate() {
+            let index = self.index(p);
+            free_after[index] = i + 1 + self.growth;
+        }
+        free_after
+    }
+
+    /// shortest way from the head to every cell it can get to, by row, as the number of moves
+    /// and the direction of the last one.  parts of the body can be gone into once they have moved on.
+    fn explore(&self) -> Vec<Option<(usize, Direction)>> {
+        let free_after = self.free_after();
+        let mut found = vec![None; free_after.len()];
+        let mut queue = VecDeque::new();
+        queue.push_back((self.head(), 0));
+        while let Some((p, moves)) = queue.pop_front() {
+            for &dir in &DIRECTIONS {
+                if moves == 0 && dir == self.direction.opposite() {
+                    continue;
+                }
+                let next = match self.topology.advance(p, dir, &self.size) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                let index = self.index(next);
+                if found[index].is_none() && free_after[index] <= moves + 1 {
+                    found[index] = Some((moves + 1, dir));
+                    queue.push_back((next, moves + 1));
+                }
+            }
+        }
+        found
+    }
+
+    /// moves from the head to `goal` along the way found by `explore`, if there is one.
+    fn path(&self, found: &[Option<(usize, Direction)>], goal: Point<isize>) -> Option<Vec<Direction>> {
+        let mut path = Vec::new();
+        let mut p = goal;
+        loop {
+            let (moves, dir) = found[self.index(p)]?;
+            path.push(dir);
+            if moves == 1 {
+                break;
+            }
+            p = self.topology.advance(p, dir.opposite(), &self.size).expect("way goes between neighbors");
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// food the head can get to in the fewest moves.
+    fn nearest_food(&self, found: &[Option<(usize, Direction)>]) -> Option<Point<isize>> {
+        found
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| self.food[i])
+            .filter_map(|(i, way)| way.map(|(moves, _)| (moves, i)))
+            .min()
+            .map(|(_, i)| self.point(i))
+    }
+
+    /// move the snake one cell, as the game would.  returns `false` if that kills it.
+    fn advance(&mut self, dir: Direction) -> bool {
+        let next = match self.topology.advance(self.head(), dir, &self.size) {
+            Some(next) => next,
+            None => return false,
+        };
+        let index = self.index(next);
+        if self.food[index] {
+            self.food[index] = false;
+            self.growth += self.food_growth;
+        }
+        if self.growth > 0 {
+            self.growth -= 1;
+        } else {
+            self.snake.pop_back();
+        }
+        if self.obstacles[index] || self.snake.contains(&next) {
+            return false;
+        }
+        self.snake.push_front(next);
+        self.direction = dir;
+        true
+    }
+
+    /// whether the head can get to where the tail is, so the snake can always follow it
+    /// and never shuts itself in.
+    fn can_reach_tail(&self) -> bool {
+        self.explore()[self.index(self.tail())].is_some()
+    }
+
+    /// direction with the most cells to get to once gone in,
+    /// to last as long as possible when nothing better is left.
+    fn roomiest(&self) -> Direction {
+        self.moves()
+            .filter_map(|dir| {
+                let mut after = self.clone();
+                if !after.advance(dir) {
+                    return None;
+                }
+                Some((after.explore().iter().filter(|way| way.is_some()).count(), dir))
+            })
+            .max_by_key(|&(room, _)| room)
+            .map_or(self.direction, |(_, dir)| dir)
+    }
+
+    /// whether every part of the snake follows the one behind it on the cycle,
+    /// so going on along the cycle is always safe.
+    fn is_on_cycle(&self, cycle: &[Direction]) -> bool {
+        self.snake.iter().zip(self.snake.iter().skip(1)).all(|(&ahead, &behind)| {
+            self.topology.advance(behind, cycle[self.index(behind)], &self.size) == Some(ahead)
+        })
+    }
+
+    /// whether the cells along the cycle are free for as long as it takes the snake,
+    /// grown to its full length, to get on it.
+    fn can_get_on_cycle(&self, cycle: &[Direction]) -> bool {
+        if cycle[self.index(self.head())] == self.direction.opposite() {
+            return false;
+        }
+        let free_after = self.free_after();
+        let mut p = self.head();
+        (1..=self.snake.len() + self.growth).all(|moves| {
+            p = self.topology.advance(p, cycle[self.index(p)], &self.size).expect("cycle goes between neighbors");
+            free_after[self.index(p)] <= moves
+        })
+    }
+}
+
+/// way to go from each cell of a field, by row, to go through all of them in a round trip.
+/// there is none on a walled field with odd width and height.
+fn hamiltonian_cycle(size: (usize, usize), topology: Topology) -> Option<Vec<Direction>> {
+    let (width, height) = size;
+    let transposed = |order: Vec<(usize, usize)>| order.into_iter().map(|(x, y)| (y, x)).collect();
+    let order: Vec<(usize, usize)> = if width * height < 2 {
+        return None;
+    } else if height == 1 || width == 1 {
+        if topology == Topology::Walled && width * height > 2 {
+            return None;
+        }
+        (0..width).flat_map(|x| (0..height).map(move |y| (x, y))).collect()
+    } else if height % 2 == 0 {
+        zigzag(width, height)
+    } else if width % 2 == 0 {
+        transposed(zigzag(height, width))
+    } else if topology == Topology::Walled {
+        return None;
+    } else if height >= width {
+        spiral(width, height)
+    } else {
+        transposed(spiral(height, width))
+    };
+    let cycle = order
+        .iter()
+        .zip(order.iter().cycle().skip(1))
+        .map(|(&(x, y), &(next_x, next_y))| {
+            let (p, next) = (Point { x: x as isize, y: y as isize }, Point { x: next_x as isize, y: next_y as isize });
+            let dir = DIRECTIONS
+                .iter()
+                .copied()
+                .find(|&dir| topology.advance(p, dir, &size) == Some(next))
+                .expect("cycle goes between neighbors");
+            (y * width + x, dir)
+        });
+    let mut directions = vec![Up; width * height];
+    for (index, dir) in cycle {
+        directions[index] = dir;
+    }
+    Some(directions)
+}
+
+/// round trip through a field with an even height: along the top row, back and forth
+/// along the others but for the first column, and up that column.
+fn zigzag(width: usize, height: usize) -> Vec<(usize, usize)> {
+    let mut order: Vec<(usize, usize)> = (0..width).map(|x| (x, 0)).collect();
+    for y in 1..height {
+        if y % 2 == 1 {
+            order.extend((1..width).rev().map(|x| (x, y)));
+        } else {
+            order.extend((1..width).map(|x| (x, y)));
+        }
+    }
+    order.extend((1..height).rev().map(|y| (0, y)));
+    order
+}
+
+/// round trip through a wrapping field with odd width and height, the height not less than the width:
+/// each row all the way round, then down to the next one.  the rows are gone along rightwards
+/// or leftwards so that the last one ends above where the first one starts.
+fn spiral(width: usize, height: usize) -> Vec<(usize, usize)> {
+    let mut order = Vec::with_capacity(width * height);
+    let mut start = 0;
+    for y in 0..height {
+        // rows going right and left make up for one another, but for `width` rows going right.
+        let step = if y < (height + width) / 2 { 1 } else { width - 1 };
+        for i in 0..width {
+            order.push(((start + i * step) % width, y));
+        }
+        start = (start + (width - 1) * step) % width;
+    }
+    order
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DeathCause, GameConfig, GameState};
+
+    /// let a bot play until the game is over or given number of ticks.
+    fn play(strategy: &mut dyn Strategy, game: &mut Game, ticks: usize) {
+        for _ in 0..ticks {
+            if game.is_over() {
+                break;
+            }
+            let dir = strategy.next_move(game);
+            game.turn(dir).ok();
+            game.step();
+        }
+    }
+
+    #[test]
+    fn cycles() {
+        let sizes = [(4, 3), (5, 4), (6, 6), (5, 5), (3, 7), (7, 3), (1, 4)];
+        for &size in &sizes {
+            let cycle = hamiltonian_cycle(size, Topology::Wrapping).unwrap();
+            let mut seen = vec![false; size.0 * size.1];
+            let mut p = Point { x: 0isize, y: 0isize };
+            for _ in 0..seen.len() {
+                let index = p.y as usize * size.0 + p.x as usize;
+                assert!(!seen[index], "{:?} goes through {:?} twice", size, p);
+                seen[index] = true;
+                p = Topology::Wrapping.advance(p, cycle[index], &size).unwrap();
+            }
+            assert_eq!(p, Point { x: 0, y: 0 }, "{:?} does not go round", size);
+        }
+        assert!(hamiltonian_cycle((4, 3), Topology::Walled).is_some());
+        assert_eq!(hamiltonian_cycle((5, 5), Topology::Walled), None);
+    }
+
+    #[test]
+    fn greedy_eats() {
+        let mut game = Game::with_config(GameConfig::new().size(10, 8).seed(7)).unwrap();
+        play(&mut GreedyBot, &mut game, 200);
+        assert!(game.score() >= 5);
+    }
+
+    #[test]
+    fn safe_survives() {
+        let config = GameConfig::new().size(8, 6).snake_len(3).topology(Topology::Walled).seed(3);
+        let mut game = Game::with_config(config).unwrap();
+        play(&mut SafeBot, &mut game, 2000);
+        assert!(game.score() >= 20);
+        assert_ne!(game.death_cause(), Some(DeathCause::Wall));
+    }
+
+    #[test]
+    fn cycle_fills_board() {
+        for &topology in &[Topology::Walled, Topology::Wrapping] {
+            let config = GameConfig::new().size(6, 5).snake_len(3).topology(topology).seed(11);
+            let mut game = Game::with_config(config).unwrap();
+            play(&mut CycleBot::new(), &mut game, 5000);
+            assert_eq!(game.state(), GameState::Won, "{:?}", topology);
+        }
+        let config = GameConfig::new().size(5, 5).snake_len(3).seed(5);
+        let mut game = Game::with_config(config).unwrap();
+        play(&mut CycleBot::new(), &mut game, 5000);
+        assert_eq!(game.state(), GameState::Won);
+    }
+}
@@ -0,0 +1,138 @@
+//! Part of a field larger than the screen which is in view, following the snake.
+
+use crate::point::*;
+
+
+/// Window onto a field too large to be shown whole, which scrolls to keep a point in view,
+/// usually the head of the snake.
+///
+/// it only moves when the point comes closer to an edge of the view than the margin,
+/// and on a wrapping field it goes on across the edges, so the field never seems to end.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Camera {
+    /// cell at the top left of the view, once it has been placed.
+    origin: Option<Point<isize>>,
+    /// cells kept between the point followed and the edges of the view,
+    /// a quarter of the view if not given.  a margin of half the view keeps the point centered.
+    pub margin: Option<usize>,
+}
+
+impl Camera {
+    pub fn new(margin: Option<usize>) -> Self {
+        Camera { origin: None, margin }
+    }
+
+    /// cell at the top left of the view, if it has been placed.
+    pub fn origin(&self) -> Option<Point<isize>> { self.origin }
+
+    /// forget where the view was, so it is centered on the point next followed.
+    pub fn reset(&mut self) {
+        self.origin = None;
+    }
+
+    /// move the view of `view` cells over a field of `size` cells, if needed to keep `target` in it.
+    /// returns the cell at its top left, which is `(0, 0)` along a side the whole field fits.
+    ///
+    /// with `wrapping` the view goes on past the edges of the field, and the origin is always
+    /// within it.  otherwise the view stays within the field.
+    pub fn follow(&mut self, target: Point<isize>, view: (usize, usize), size: (usize, usize), wrapping: bool)
+        -> Point<isize>
+    {
+        let origin = self.origin;
+        let axis = |origin: Option<isize>, target: isize, view: usize, size: usize| {
+            follow_axis(origin, target, view, size, self.margin, wrapping)
+        };
+        let origin = Point {
+            x: axis(origin.map(|p| p.x), target.x, view.0, size.0),
+            y: axis(origin.map(|p| p.y), target.y, view.1, size.1),
+        };
+        self.origin = Some(origin);
+        origin
+    }
+}
+
+/// first cell in view along one side.
+fn follow_axis(origin: Option<isize>, target: isize, view: usize, size: usize, margin: Option<usize>, wrapping: bool)
+    -> isize
+{
+    if view >= size || view == 0 {
+        return 0;
+    }
+    let (view, size) = (view as isize, size as isize);
+    // at least the cell the target is in stays between the margins.
+    let margin = (margin.unwrap_or(view as usize / 4) as isize).min((view - 1) / 2);
+    let origin = match origin {
+        None => target - view / 2,
+        Some(origin) => {
+            let mut offset = target - origin;
+            if wrapping {
+                // out of view the target is behind whichever edge is nearer.
+                offset = offset.rem_euclid(size);
+                if offset >= (size + view) / 2 {
+                    offset -= size;
+                }
+            }
+            if offset < margin {
+                target - margin
+            } else if offset > view - 1 - margin {
+                target - (view - 1 - margin)
+            } else {
+                origin
+            }
+        },
+    };
+    if wrapping {
+        origin.rem_euclid(size)
+    } else {
+        origin.max(0).min(size - view)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: isize, y: isize) -> Point<isize> { Point { x, y } }
+
+    #[test]
+    fn scrolling() {
+        let mut camera = Camera::new(Some(2));
+        // centered at first, then still until the target is within the margin.
+        assert_eq!(camera.follow(point(50, 5), (20, 10), (100, 10), false), point(40, 0));
+        assert_eq!(camera.follow(point(57, 5), (20, 10), (100, 10), false), point(40, 0));
+        assert_eq!(camera.follow(point(58, 5), (20, 10), (100, 10), false), point(41, 0));
+        assert_eq!(camera.follow(point(41, 5), (20, 10), (100, 10), false), point(39, 0));
+        // stops at the edge of the field.
+        assert_eq!(camera.follow(point(1, 5), (20, 10), (100, 10), false), point(0, 0));
+        assert_eq!(camera.follow(point(99, 5), (20, 10), (100, 10), false), point(80, 0));
+        camera.reset();
+        assert_eq!(camera.origin(), None);
+    }
+
+    #[test]
+    fn wrapping() {
+        let mut camera = Camera::new(Some(2));
+        assert_eq!(camera.follow(point(97, 0), (20, 10), (100, 50), true), point(87, 45));
+        // across the right edge the view goes on from the left one.
+        assert_eq!(camera.follow(point(4, 0), (20, 10), (100, 50), true), point(87, 45));
+        assert_eq!(camera.follow(point(5, 0), (20, 10), (100, 50), true), point(88, 45));
+        // and back.
+        assert_eq!(camera.follow(point(89, 0), (20, 10), (100, 50), true), point(87, 45));
+        assert_eq!(camera.follow(point(80, 0), (20, 10), (100, 50), true), point(78, 45));
+        // down across the bottom edge.
+        assert_eq!(camera.follow(point(80, 3), (20, 10), (100, 50), true), point(78, 46));
+    }
+
+    #[test]
+    fn margins() {
+        // the target stays in the middle with a margin of half the view.
+        let mut camera = Camera::new(Some(100));
+        assert_eq!(camera.follow(point(30, 0), (21, 5), (100, 5), false), point(20, 0));
+        assert_eq!(camera.follow(point(31, 0), (21, 5), (100, 5), false), point(21, 0));
+        // and goes right to the edge of the view with none.
+        let mut camera = Camera::new(Some(0));
+        camera.follow(point(30, 0), (21, 5), (100, 5), false);
+        assert_eq!(camera.follow(point(40, 0), (21, 5), (100, 5), false), point(20, 0));
+        assert_eq!(camera.follow(point(41, 0), (21, 5), (100, 5), false), point(21, 0));
+    }
+}
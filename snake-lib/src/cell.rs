@@ -1,8 +1,6 @@
 use std::fmt;
 
 use num_traits::{cast, NumCast};
-#[cfg(feature = "pancurses")]
-use pancurses::Input;
 
 use crate::point::*;
 pub use self::Direction::*;
@@ -12,25 +10,36 @@ pub use Cell::*;
 pub enum Cell {
     Snake(Direction),
     Food,
+    /// solid wall, e.g. level boundaries and partitions.
+    Wall,
+    /// standalone static blocker.
+    Rock,
     Empty
 }
 
 impl Cell {
     pub fn snake_direction(&self) -> Option<Direction> {
         match *self {
-            Snake(ref dir) => Some(dir.clone()),
+            Snake(dir) => Some(dir),
             _ => Option::None
         }
     }
+
+    /// whether touching this cell kills the snake.
+    pub fn is_obstacle(&self) -> bool {
+        matches!(*self, Wall | Rock)
+    }
 }
 
 impl fmt::Display for Cell {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.snake_direction()
-            .and_then(|dir| Some(dir.fmt(f)))
+            .map(|dir| dir.fmt(f))
             .or_else(|| {
                 Some(write!(f, "{}", match *self {
                     Food => "*",
+                    Wall => "#",
+                    Rock => "o",
                     Empty => " ",
                     _ => unreachable!(),
                 }))
@@ -48,17 +57,6 @@ pub enum Direction {
 }
 
 impl Direction {
-    #[cfg(feature = "pancurses")]
-    pub fn from_input(key: Input) -> Option<Self> {
-        Some(match key {
-            Input::Character('s') | Input::KeyDown =>  Down,
-            Input::Character('w') | Input::KeyUp => Up,
-            Input::Character('a') | Input::KeyLeft => Left,
-            Input::Character('d') | Input::KeyRight => Right,
-            _ => return None,
-        })
-    }
-
     pub fn advance<T: NumCast>(&self, point: Point<T>) -> Point<T> {
         match *self {
             Up => Point { y: cast(point.y.to_isize().unwrap() - 1).unwrap(), ..point },
@@ -76,6 +74,21 @@ impl Direction {
             Right => Left,
         }
     }
+
+    /// direction after turning left, as seen by the snake.
+    pub fn turn_left(&self) -> Direction {
+        match *self {
+            Up => Left,
+            Left => Down,
+            Down => Right,
+            Right => Up,
+        }
+    }
+
+    /// direction after turning right, as seen by the snake.
+    pub fn turn_right(&self) -> Direction {
+        self.turn_left().opposite()
+    }
 }
 
 impl fmt::Display for Direction {
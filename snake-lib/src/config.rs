@@ -0,0 +1,234 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::cell::*;
+use crate::map::*;
+use crate::point::*;
+use crate::topology::*;
+
+
+/// Parameters of a new game.
+///
+/// Built with chained setters on top of the defaults, which reproduce the classic setup:
+///
+/// ```
+/// use snake::*;
+///
+/// let config = GameConfig::new()
+///     .size(40, 20)
+///     .snake_len(3)
+///     .direction(Direction::Up);
+/// let game = Game::with_config(config).unwrap();
+/// assert_eq!(game.field().size(), (40, 20));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    pub width: usize,
+    pub height: usize,
+    pub snake_len: usize,
+    pub start: Option<Point<isize>>,
+    pub direction: Direction,
+    pub growth: usize,
+    pub topology: Topology,
+    /// level to play on.  overrides field size and snake spawn.
+    pub map: Option<Map>,
+    /// seed for food placement.  random if not set.
+    pub seed: Option<u64>,
+}
+
+impl GameConfig {
+    pub fn new() -> Self {
+        GameConfig {
+            width: 20,
+            height: 10,
+            snake_len: 5,
+            start: None,
+            direction: Right,
+            growth: 1,
+            topology: Topology::Wrapping,
+            map: None,
+            seed: None,
+        }
+    }
+
+    /// field dimensions.
+    pub fn size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// initial length of the snake, including its head.
+    pub fn snake_len(mut self, len: usize) -> Self {
+        self.snake_len = len;
+        self
+    }
+
+    /// position of the snake's head.  by default the snake is centered on the field.
+    pub fn start(mut self, head: Point<isize>) -> Self {
+        self.start = Some(head);
+        self
+    }
+
+    /// direction the snake is heading at the start.  its body extends the opposite way.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// number of segments the snake grows by for each food eaten.
+    pub fn growth(mut self, growth: usize) -> Self {
+        self.growth = growth;
+        self
+    }
+
+    /// whether the field wraps around its edges or is surrounded by walls.
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// play on a level loaded from a map.
+    pub fn map(mut self, map: Map) -> Self {
+        let (width, height) = map.field().size();
+        self.width = width;
+        self.height = height;
+        self.snake_len = map.snake_len();
+        self.start = Some(map.head());
+        self.direction = map.direction();
+        self.map = Some(map);
+        self
+    }
+
+    /// seed for food placement.  same seed and same inputs always produce the same game.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// position of the snake's head, either explicit or centered.
+    pub fn head(&self) -> Point<isize> {
+        if let Some(ref map) = self.map {
+            return map.head();
+        }
+        if let Some(start) = self.start {
+            return start;
+        }
+        let (w, h, len) = (self.width as isize, self.height as isize, self.snake_len as isize);
+        let centered = |extent: isize| ((extent + len) / 2).min(extent - 1);
+        match self.direction {
+            Right => Point { x: centered(w), y: h / 2 },
+            Left => Point { x: w - 1 - centered(w), y: h / 2 },
+            Down => Point { x: w / 2, y: centered(h) },
+            Up => Point { x: w / 2, y: h - 1 - centered(h) },
+        }
+    }
+
+    /// position of the snake's tail at the start.
+    pub fn tail(&self) -> Point<isize> {
+        if let Some(ref map) = self.map {
+            return map.tail();
+        }
+        let mut tail = self.head();
+        let back = self.direction.opposite();
+        for _ in 1..self.snake_len {
+            tail = back.advance(tail);
+        }
+        tail
+    }
+
+    /// check that a game can be started with this configuration.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(ref map) = self.map {
+            return map.validate(self.topology).map_err(ConfigError::Map);
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(ConfigError::EmptyField);
+        }
+        if self.snake_len == 0 {
+            return Err(ConfigError::NoSnake);
+        }
+        if self.snake_len >= self.width * self.height {
+            return Err(ConfigError::NoRoomForFood);
+        }
+        let size = (self.width, self.height);
+        if !self.head().is_inside(&size) {
+            return Err(ConfigError::StartOutOfBounds);
+        }
+        if !self.tail().is_inside(&size) {
+            return Err(ConfigError::SnakeDoesNotFit);
+        }
+        Ok(())
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self { Self::new() }
+}
+
+/// Reasons a `GameConfig` is rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// width or height is zero.
+    EmptyField,
+    /// snake length is zero.
+    NoSnake,
+    /// the snake would cover the whole field, leaving no place for food.
+    NoRoomForFood,
+    /// head position lies outside of the field.
+    StartOutOfBounds,
+    /// snake's body does not fit between its head and the edge of the field.
+    SnakeDoesNotFit,
+    /// level map is not playable.
+    Map(MapError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let ConfigError::Map(ref err) = *self {
+            return write!(f, "invalid map: {}", err);
+        }
+        write!(f, "{}", match *self {
+            ConfigError::EmptyField => "field must be at least 1x1",
+            ConfigError::NoSnake => "snake must be at least 1 segment long",
+            ConfigError::NoRoomForFood => "snake must leave at least one free cell",
+            ConfigError::StartOutOfBounds => "start position is outside of the field",
+            ConfigError::SnakeDoesNotFit => "snake does not fit between start position and the edge",
+            ConfigError::Map(_) => unreachable!(),
+        })
+    }
+}
+
+impl Error for ConfigError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_is_valid() {
+        let config = GameConfig::default();
+        assert_eq!(config.validate(), Ok(()));
+        assert_eq!(config.head(), Point { x: 12, y: 5 });
+        assert_eq!(config.tail(), Point { x: 8, y: 5 });
+    }
+
+    #[test]
+    fn centered_in_every_direction() {
+        for &dir in &[Up, Down, Left, Right] {
+            let config = GameConfig::new().size(7, 7).snake_len(7).direction(dir);
+            assert_eq!(config.validate(), Ok(()), "{:?}", dir);
+        }
+    }
+
+    #[test]
+    fn invalid_configs() {
+        assert_eq!(GameConfig::new().size(0, 3).validate(), Err(ConfigError::EmptyField));
+        assert_eq!(GameConfig::new().snake_len(0).validate(), Err(ConfigError::NoSnake));
+        assert_eq!(GameConfig::new().size(5, 1).validate(), Err(ConfigError::NoRoomForFood));
+        assert_eq!(GameConfig::new().start(Point { x: 20, y: 0 }).validate(),
+                   Err(ConfigError::StartOutOfBounds));
+        assert_eq!(GameConfig::new().start(Point { x: 3, y: 0 }).validate(),
+                   Err(ConfigError::SnakeDoesNotFit));
+    }
+}
@@ -0,0 +1,21 @@
+use crate::cell::*;
+use crate::point::*;
+use crate::DeathCause;
+
+
+/// Something that happened during a single tick of the game, as returned by `Game::tick`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// head moved one cell in given direction.
+    Moved { from: Point<isize>, to: Point<isize>, direction: Direction },
+    /// food at given position was eaten.
+    AteFood(Point<isize>),
+    /// snake grew by one segment, up to the given length.
+    Grew(usize),
+    /// snake died, the game is over.
+    Died(DeathCause),
+    /// new food appeared at given position.
+    FoodSpawned(Point<isize>),
+    /// no free cell is left for new food.
+    BoardFull,
+}
@@ -1,14 +1,15 @@
 use std::fmt;
-use std::iter;
 use std::ops::{Index, IndexMut};
 
 use rand::Rng;
-use num_traits::{cast, NumCast};
+use num_traits::NumCast;
 
 use crate::point::*;
 use crate::cell::*;
+use crate::topology::*;
 
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct Field {
     // first y, then x
     inner: Vec<Vec<Cell>>,
@@ -28,22 +29,21 @@ impl Field {
         Field { inner: rows }
     }
 
-    /// put a snake on the game field.
+    /// put a snake on the game field, heading in given `direction`.
+    /// its body extends from the `head` in the opposite direction.
     /// returns its head and tail positions.
-    pub fn init_snake<T>(&mut self, len: usize) -> (Point<T>, Point<T>)
+    pub fn init_snake<T>(&mut self, head: Point<T>, direction: Direction, len: usize) -> (Point<T>, Point<T>)
     where T: NumCast + Clone {
-        assert!(len <= self.width());
+        assert!(len > 0);
 
-        let head = Point::<T> {
-            x: cast((self.width() + len) / 2).unwrap(),
-            y: cast(self.height() / 2).unwrap(),
-        };
+        let back = direction.opposite();
         let mut tail = head.clone();
-        for _ in 0..len {
-            self[tail.clone()] = Snake(Right);
-            tail.x = cast(tail.x.to_isize().unwrap() - 1).unwrap();
+        self[tail.clone()] = Snake(direction);
+        for _ in 1..len {
+            tail = back.advance(tail);
+            assert!(tail.is_inside(&self.size()), "snake does not fit on the field");
+            self[tail.clone()] = Snake(direction);
         }
-        tail.x = cast(tail.x.to_isize().unwrap() + 1).unwrap();
         (head, tail)
     }
 
@@ -54,33 +54,53 @@ impl Field {
 
     pub fn rows(&self) -> &Vec<Vec<Cell>> { &self.inner }
 
-    pub fn next_point<T>(&self, p: Point<T>) -> Option<Point<T>>
+    /// follow the snake part at `p` to the next one, towards the head.
+    pub fn next_point<T>(&self, p: Point<T>, topology: Topology) -> Option<Point<T>>
         where T: NumCast {
         self[&p]
             .snake_direction()
-            .map(|d| d.advance(p))
-            .map(|p| p.wrap(&self.size()))
+            .and_then(|d| topology.advance(p, d, &self.size()))
     }
 
-    pub fn drop_food(&mut self, snake_len: usize) -> Result<(), ()> {
-        let n_free = self.width() * self.height() - snake_len as usize;
-        let place = rand::thread_rng().gen_range(0, n_free);
+    /// number of empty cells.
+    pub fn free_cells(&self) -> usize {
+        self.inner
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter(|cell| matches!(*cell, Cell::Empty))
+            .count()
+    }
+
+    /// whether there is any food on the field.
+    pub fn has_food(&self) -> bool {
+        self.inner.iter().flatten().any(|cell| matches!(*cell, Cell::Food))
+    }
+
+    /// place food on a random empty cell, picked with given random number generator.
+    /// returns position of the food, or `None` if there are no empty cells left.
+    pub fn drop_food<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<Point<isize>> {
+        let width = self.width();
+        let n_free = self.free_cells();
+        if n_free == 0 {
+            return None;
+        }
+        let place = rng.gen_range(0, n_free);
         self.inner
             .iter_mut()
             .flat_map(|row| row.iter_mut())
-            .filter(|cell| matches!(*cell, Cell::Empty))
+            .enumerate()
+            .filter(|(_, cell)| matches!(**cell, Cell::Empty))
             .nth(place)
-            .map(|cell: &mut Cell| *cell = Cell::Food)
-            .ok_or(())
+            .map(|(i, cell)| {
+                *cell = Cell::Food;
+                Point { x: (i % width) as isize, y: (i / width) as isize }
+            })
     }
 }
 
 impl fmt::Display for Field {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let h_border = format!("+{}+",
-                               iter::repeat("-")
-                                   .take(self.width())
-                                   .collect::<String>());
+        let h_border = format!("+{}+", "-".repeat(self.width()));
         writeln!(
             f, "{}\n{}\n{}",
             h_border,
@@ -146,7 +166,7 @@ impl<'a, T: NumCast> Index<&'a Point<T>> for Field {
 }
 
 impl<'a, T: NumCast> IndexMut<&'a Point<T>> for Field {
-    fn index_mut(&mut self, index: &Point<T>) -> &mut Self::Output {
+    fn index_mut(&mut self, index: &'a Point<T>) -> &mut Self::Output {
         &mut self.inner[index.y.to_usize().unwrap()][index.x.to_usize().unwrap()]
     }
 }
@@ -0,0 +1,508 @@
+//! Drawing and input which do not depend on any terminal library.
+//!
+//! Widgets draw themselves into a `Frame`, a grid of styled characters,
+//! and a `Frontend` shows frames and reads `Key`s from whatever it runs on.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::cell::*;
+
+
+/// Colors every frontend is expected to show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Color {
+    /// whatever the frontend uses by default.
+    #[default]
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+/// How a character is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    pub fg: Color,
+    pub bg: Color,
+    pub bold: bool,
+    /// swap foreground and background.
+    pub reverse: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style { fg: Color::Default, bg: Color::Default, bold: false, reverse: false };
+
+    pub fn fg(color: Color) -> Style {
+        Style { fg: color, ..Style::PLAIN }
+    }
+
+    pub fn bold(self) -> Style {
+        Style { bold: true, ..self }
+    }
+
+    pub fn reverse(self) -> Style {
+        Style { reverse: true, ..self }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StyledCell {
+    pub ch: char,
+    pub style: Style,
+}
+
+impl StyledCell {
+    /// stands for the right half of a wide character, such as an emoji, in the cell to its left.
+    /// frontends draw nothing for it.
+    pub const CONTINUATION: char = '\0';
+}
+
+impl Default for StyledCell {
+    fn default() -> Self {
+        StyledCell { ch: ' ', style: Style::PLAIN }
+    }
+}
+
+/// Characters a box is drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Border {
+    pub horizontal: char,
+    pub vertical: char,
+    /// top left, top right, bottom left and bottom right.
+    pub corners: [char; 4],
+}
+
+impl Border {
+    /// thin line of box-drawing characters.
+    pub const LINE: Border = Border { horizontal: '─', vertical: '│', corners: ['┌', '┐', '└', '┘'] };
+    pub const ASCII: Border = Border { horizontal: '-', vertical: '|', corners: ['+', '+', '+', '+'] };
+
+    /// border made of a single character all around.
+    pub const fn solid(ch: char) -> Border {
+        Border { horizontal: ch, vertical: ch, corners: [ch; 4] }
+    }
+}
+
+/// Rectangular grid of styled characters, the unit frontends show.
+///
+/// Drawing outside of the frame is silently clipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<StyledCell>,
+}
+
+impl Frame {
+    /// blank frame of given size.
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame { width, height, cells: vec![StyledCell::default(); width * height] }
+    }
+
+    pub fn width(&self) -> usize { self.width }
+
+    pub fn height(&self) -> usize { self.height }
+
+    /// size as `(width, height)`.
+    pub fn size(&self) -> (usize, usize) { (self.width, self.height) }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<StyledCell> {
+        if x < self.width && y < self.height {
+            Some(self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn put(&mut self, x: isize, y: isize, ch: char, style: Style) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.cells[y as usize * self.width + x as usize] = StyledCell { ch, style };
+        }
+    }
+
+    /// character two columns wide, such as an emoji, at given position and the one to its right.
+    pub fn put_wide(&mut self, x: isize, y: isize, ch: char, style: Style) {
+        self.put(x, y, ch, style);
+        self.put(x + 1, y, StyledCell::CONTINUATION, style);
+    }
+
+    /// text on a single line, starting at given position.
+    pub fn print(&mut self, x: isize, y: isize, text: &str, style: Style) {
+        for (i, ch) in text.chars().enumerate() {
+            self.put(x + i as isize, y, ch, style);
+        }
+    }
+
+    /// text on a single line, centered horizontally.
+    pub fn print_centered(&mut self, y: isize, text: &str, style: Style) {
+        let x = (self.width as isize - text.chars().count() as isize) / 2;
+        self.print(x.max(0), y, text, style);
+    }
+
+    /// outline of a box, with its top left corner at given position.
+    pub fn draw_box(&mut self, x: isize, y: isize, width: usize, height: usize, border: &Border, style: Style) {
+        if width == 0 || height == 0 { return }
+        let (right, bottom) = (x + width as isize - 1, y + height as isize - 1);
+        for i in x..=right {
+            self.put(i, y, border.horizontal, style);
+            self.put(i, bottom, border.horizontal, style);
+        }
+        for j in y..=bottom {
+            self.put(x, j, border.vertical, style);
+            self.put(right, j, border.vertical, style);
+        }
+        let [top_left, top_right, bottom_left, bottom_right] = border.corners;
+        self.put(x, y, top_left, style);
+        self.put(right, y, top_right, style);
+        self.put(x, bottom, bottom_left, style);
+        self.put(right, bottom, bottom_right, style);
+    }
+
+    /// outline of the whole frame.
+    pub fn draw_border(&mut self, border: &Border, style: Style) {
+        self.draw_box(0, 0, self.width, self.height, border, style);
+    }
+
+    /// copy another frame on top of this one, with its top left corner at given position.
+    pub fn blit(&mut self, other: &Frame, x: isize, y: isize) {
+        for j in 0..other.height {
+            for i in 0..other.width {
+                let cell = other.cells[j * other.width + i];
+                self.put(x + i as isize, y + j as isize, cell.ch, cell.style);
+            }
+        }
+    }
+
+    /// copy another frame into the middle of this one.
+    /// returns `false` and copies nothing if it does not fit.
+    pub fn blit_centered(&mut self, other: &Frame) -> bool {
+        if other.width > self.width || other.height > self.height {
+            return false;
+        }
+        let x = (self.width - other.width) / 2;
+        let y = (self.height - other.height) / 2;
+        self.blit(other, x as isize, y as isize);
+        true
+    }
+
+    /// position of the top left corner of `other`, if it was centered in this frame.
+    pub fn center_of(&self, other: (usize, usize)) -> (isize, isize) {
+        ((self.width as isize - other.0 as isize) / 2, (self.height as isize - other.1 as isize) / 2)
+    }
+}
+
+/// frame as plain text, one line per row, without styles.
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            let line: String = row
+                .iter()
+                .map(|cell| cell.ch)
+                .filter(|&ch| ch != StyledCell::CONTINUATION)
+                .collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Key press, or other input event, as reported by a frontend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Escape,
+    Backspace,
+    /// screen changed its size, so everything should be laid out again.
+    Resize,
+}
+
+const KEY_NAMES: [(Key, &str); 8] = [
+    (Key::Up, "up"),
+    (Key::Down, "down"),
+    (Key::Left, "left"),
+    (Key::Right, "right"),
+    (Key::Enter, "enter"),
+    (Key::Escape, "escape"),
+    (Key::Backspace, "backspace"),
+    (Key::Char(' '), "space"),
+];
+
+impl Key {
+    /// key with given name: a single character, or one of `up`, `down`, `left`, `right`,
+    /// `enter`, `escape`, `backspace` and `space`, in any case.
+    pub fn from_name(name: &str) -> Option<Key> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(Key::Char(c));
+        }
+        let name = name.to_lowercase();
+        KEY_NAMES.iter().find(|&&(_, n)| n == name).map(|&(key, _)| key)
+    }
+
+    /// name for `from_name`.
+    pub fn name(self) -> String {
+        match KEY_NAMES.iter().find(|&&(k, _)| k == self) {
+            Some(&(_, name)) => name.to_string(),
+            None => match self {
+                Key::Char(c) => c.to_string(),
+                _ => "resize".to_string(),
+            },
+        }
+    }
+}
+
+/// What the player wants to do in the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Turn(Direction),
+    /// turn left of where the snake is heading.
+    TurnLeft,
+    /// turn right of where the snake is heading.
+    TurnRight,
+    Pause,
+    Quit,
+    /// start a new game right away.
+    Restart,
+    /// go a speed level faster.
+    SpeedUp,
+    /// hand the game over to a bot, or take it back.
+    Autopilot,
+}
+
+impl Action {
+    /// every action, in the order they are listed to the player.
+    pub const ALL: [Action; 11] = [
+        Action::Turn(Up),
+        Action::Turn(Down),
+        Action::Turn(Left),
+        Action::Turn(Right),
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Pause,
+        Action::Quit,
+        Action::Restart,
+        Action::SpeedUp,
+        Action::Autopilot,
+    ];
+
+    /// name of the action in config files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Turn(Up) => "up",
+            Action::Turn(Down) => "down",
+            Action::Turn(Left) => "left",
+            Action::Turn(Right) => "right",
+            Action::TurnLeft => "turn-left",
+            Action::TurnRight => "turn-right",
+            Action::Pause => "pause",
+            Action::Quit => "quit",
+            Action::Restart => "restart",
+            Action::SpeedUp => "speed-up",
+            Action::Autopilot => "autopilot",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|action| action.name() == name)
+    }
+}
+
+/// Keys bound to actions.  each key does at most one thing, while an action can have several keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl KeyBindings {
+    /// names of presets, for `preset`.
+    pub const PRESETS: [&'static str; 2] = ["default", "vim"];
+
+    /// no keys bound at all.
+    pub fn empty() -> Self {
+        KeyBindings { bindings: Vec::new() }
+    }
+
+    /// like the default, with HJKL to turn instead of WASD.
+    pub fn vim() -> Self {
+        let mut bindings = KeyBindings::default();
+        for (c, dir) in [('h', Left), ('j', Down), ('k', Up), ('l', Right)] {
+            bindings.bind(Key::Char(c), Action::Turn(dir));
+        }
+        for c in ['w', 'a', 's', 'd'] {
+            bindings.unbind(Key::Char(c));
+        }
+        bindings
+    }
+
+    /// one of `PRESETS`, by name.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(KeyBindings::default()),
+            "vim" => Some(KeyBindings::vim()),
+            _ => None,
+        }
+    }
+
+    /// bind a key to an action, instead of what it did before.
+    pub fn bind(&mut self, key: Key, action: Action) {
+        self.unbind(key);
+        self.bindings.push((key, action));
+    }
+
+    pub fn unbind(&mut self, key: Key) {
+        self.bindings.retain(|&(k, _)| k != key);
+    }
+
+    /// unbind every key of an action.
+    pub fn clear(&mut self, action: Action) {
+        self.bindings.retain(|&(_, a)| a != action);
+    }
+
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.bindings.iter().find(|&&(k, _)| k == key).map(|&(_, action)| action)
+    }
+
+    /// keys of an action, in the order they were bound.
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = Key> + '_ {
+        self.bindings.iter().filter(move |&&(_, a)| a == action).map(|&(key, _)| key)
+    }
+}
+
+/// default controls: arrows or WASD, Z and X to turn relative to the snake,
+/// Space to pause, Q to quit, R to restart, + to speed up and B for the autopilot.
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut bindings = KeyBindings::empty();
+        let keys = [
+            (Key::Up, Action::Turn(Up)),
+            (Key::Char('w'), Action::Turn(Up)),
+            (Key::Down, Action::Turn(Down)),
+            (Key::Char('s'), Action::Turn(Down)),
+            (Key::Left, Action::Turn(Left)),
+            (Key::Char('a'), Action::Turn(Left)),
+            (Key::Right, Action::Turn(Right)),
+            (Key::Char('d'), Action::Turn(Right)),
+            (Key::Char('z'), Action::TurnLeft),
+            (Key::Char('x'), Action::TurnRight),
+            (Key::Char(' '), Action::Pause),
+            (Key::Char('q'), Action::Quit),
+            (Key::Char('r'), Action::Restart),
+            (Key::Char('+'), Action::SpeedUp),
+            (Key::Char('b'), Action::Autopilot),
+        ];
+        for (key, action) in keys {
+            bindings.bind(key, action);
+        }
+        bindings
+    }
+}
+
+/// Something that can be drawn into a frame.
+pub trait Widget {
+    /// size the widget needs, as `(width, height)`.
+    fn size(&self) -> (usize, usize);
+
+    /// draw the widget into a frame of its size.
+    fn draw(&self, frame: &mut Frame);
+
+    /// the widget in a frame of its own.
+    fn render(&self) -> Frame {
+        let (width, height) = self.size();
+        let mut frame = Frame::new(width, height);
+        self.draw(&mut frame);
+        frame
+    }
+}
+
+/// Screen and keyboard of the game, be it a terminal or a window.
+pub trait Frontend {
+    /// size of the screen, as `(width, height)`.
+    fn size(&self) -> (usize, usize);
+
+    /// show a frame filling the whole screen.
+    fn show(&mut self, frame: &Frame);
+
+    /// wait for the next key, but no longer than `timeout` if there is one.
+    fn read_key(&mut self, timeout: Option<Duration>) -> Option<Key>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn drawing_is_clipped() {
+        let mut frame = Frame::new(5, 3);
+        frame.draw_box(0, 0, 5, 3, &Border::ASCII, Style::PLAIN);
+        frame.print(3, 1, "long", Style::fg(Color::Red));
+        frame.put(-1, 7, '?', Style::PLAIN);
+        assert_eq!(frame.to_string(), "+---+\n|  lo\n+---+\n");
+        assert_eq!(frame.get(3, 1), Some(StyledCell { ch: 'l', style: Style::fg(Color::Red) }));
+        assert_eq!(frame.get(5, 1), None);
+    }
+
+    #[test]
+    fn blit_centered() {
+        let mut frame = Frame::new(6, 3);
+        let mut dot = Frame::new(2, 1);
+        dot.print(0, 0, "ab", Style::PLAIN);
+        assert!(frame.blit_centered(&dot));
+        assert_eq!(frame.to_string(), "      \n  ab  \n      \n");
+        assert!(!dot.blit_centered(&frame));
+    }
+
+    #[test]
+    fn wide_characters() {
+        let mut frame = Frame::new(4, 1);
+        frame.put_wide(1, 0, '🍎', Style::PLAIN);
+        assert_eq!(frame.get(2, 0).map(|cell| cell.ch), Some(StyledCell::CONTINUATION));
+        assert_eq!(frame.to_string(), " 🍎 \n");
+    }
+
+    #[test]
+    fn default_controls() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.action(Key::Char('a')), Some(Action::Turn(Left)));
+        assert_eq!(bindings.action(Key::Down), Some(Action::Turn(Down)));
+        assert_eq!(bindings.action(Key::Enter), None);
+        assert_eq!(bindings.keys(Action::Quit).collect::<Vec<_>>(), [Key::Char('q')]);
+    }
+
+    #[test]
+    fn rebinding() {
+        let mut bindings = KeyBindings::vim();
+        assert_eq!(bindings.action(Key::Char('k')), Some(Action::Turn(Up)));
+        assert_eq!(bindings.action(Key::Char('w')), None);
+        // a key does one thing only.
+        bindings.bind(Key::Char('q'), Action::Pause);
+        assert_eq!(bindings.action(Key::Char('q')), Some(Action::Pause));
+        assert_eq!(bindings.keys(Action::Quit).count(), 0);
+        bindings.clear(Action::Pause);
+        assert_eq!(bindings.action(Key::Char(' ')), None);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Key::from_name("Up"), Some(Key::Up));
+        assert_eq!(Key::from_name("space"), Some(Key::Char(' ')));
+        assert_eq!(Key::from_name("Q"), Some(Key::Char('Q')));
+        assert_eq!(Key::from_name("nope"), None);
+        for key in [Key::Escape, Key::Char(' '), Key::Char('+')] {
+            assert_eq!(Key::from_name(&key.name()), Some(key));
+        }
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+    }
+}
@@ -1,19 +1,62 @@
 mod field;
 mod point;
 mod cell;
+mod config;
+mod topology;
+mod map;
+mod rng;
+mod serial;
+mod replay;
+mod save;
+mod scores;
+mod segment;
+mod camera;
+mod bot;
+mod events;
+mod frontend;
 
+use std::collections::VecDeque;
 use std::fmt;
 
+use rand::Rng;
+
 pub use crate::point::*;
 pub use crate::field::*;
 pub use crate::cell::*;
+pub use crate::config::*;
+pub use crate::topology::*;
+pub use crate::map::*;
+pub use crate::rng::*;
+pub use crate::serial::ParseError;
+pub use crate::replay::*;
+pub use crate::scores::*;
+pub use crate::segment::*;
+pub use crate::camera::*;
+pub use crate::bot::*;
+pub use crate::events::*;
+pub use crate::frontend::*;
 
+/// most turns that can wait for their tick at once.
+pub const TURN_QUEUE_LEN: usize = 3;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GameState {
     GameOn,
     GamePaused,
     GameOver,
+    /// snake has filled the whole field.
+    Won,
+}
+
+/// What killed the snake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    /// snake bit its own body.
+    SelfBite,
+    /// snake crashed into the edge of a walled field.
+    Wall,
+    /// snake crashed into a wall or rock cell.
+    Obstacle,
 }
 
 pub struct Game {
@@ -23,93 +66,188 @@ pub struct Game {
     score: usize,
     snake_len: usize,
     state: GameState,
-    no_turn_back: Direction,
+    death_cause: Option<DeathCause>,
+    /// turns waiting to be made, one per tick, oldest first.
+    turn_queue: VecDeque<Direction>,
+    /// segments yet to be grown from eaten food.
+    pending_growth: usize,
+    config: GameConfig,
+    seed: u64,
+    rng: GameRng,
+    /// number of steps made so far.
+    ticks: u64,
+    /// successful turns, each with the tick it was made before.
+    turns: Vec<(u64, Direction)>,
 }
 
 impl Game {
     pub fn new() -> Self {
-        let (width, height) = (20, 10);
-        let mut f = Field::with_size(width, height);
-        let snake_len = 5;
-        let (head, tail) = f.init_snake::<isize>(snake_len);
-        f.drop_food(snake_len).unwrap();
-        Game {
+        Self::with_config(GameConfig::default()).unwrap()
+    }
+
+    /// start a game with default configuration and given seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_config(GameConfig::new().seed(seed)).unwrap()
+    }
+
+    pub fn with_config(config: GameConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = GameRng::new(seed);
+        let (mut f, head, tail) = match config.map {
+            Some(ref map) => (map.field().clone(), map.head(), map.tail()),
+            None => {
+                let mut f = Field::with_size(config.width, config.height);
+                let (head, tail) = f.init_snake(config.head(), config.direction, config.snake_len);
+                (f, head, tail)
+            },
+        };
+        if !f.has_food() {
+            f.drop_food(&mut rng);
+        }
+        Ok(Game {
             field: f,
-            tail: tail,
-            head: head,
+            tail,
+            head,
             score: 0,
-            snake_len: snake_len,
+            snake_len: config.snake_len,
             state: GameState::GameOn,
-            no_turn_back: Direction::Left,
-        }
+            death_cause: None,
+            turn_queue: VecDeque::new(),
+            pending_growth: 0,
+            config,
+            seed,
+            rng,
+            ticks: 0,
+            turns: Vec::new(),
+        })
     }
 
+    /// advance the game by one tick.  returns the resulting state.
     pub fn step(&mut self) -> GameState {
-        if !matches!(self.state, GameState::GameOn) { return self.state }
+        self.tick();
+        self.state
+    }
+
+    /// advance the game by one tick.  returns what happened during it.
+    pub fn tick(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        if !matches!(self.state, GameState::GameOn) { return events }
+        self.ticks += 1;
 
-        let next_point = self.field[&self.head]
-            .clone()
-            .snake_direction()
-            .unwrap()
-            .advance(self.head.clone())
-            .wrap(&self.field.size());
-        let next_cell = self.field[&next_point].clone();
+        if let Some(direction) = self.turn_queue.pop_front() {
+            self.field[&self.head] = Cell::Snake(direction);
+        }
+        let direction = self.direction();
+        let next_point = match self.config.topology.advance(self.head, direction, &self.field.size()) {
+            Some(p) => p,
+            None => return self.die(DeathCause::Wall),
+        };
+        let next_cell = self.field[&next_point];
+
+        let ate = matches!(next_cell, Food);
+        if ate {
+            self.score += 1;
+            self.pending_growth += self.config.growth;
+        }
+        let growing = self.pending_growth > 0;
 
         match next_cell {
-            Empty => {
-                self.move_tail();
-                self.move_head()
-            },
-            Snake(_) if next_point == self.tail => {
-                self.move_tail();
-                self.move_head()
-            },
-            Snake(_) => {
-                self.state = GameState::GameOver;
-            },
-            Food => {
-                self.move_head();
-                self.snake_len += 1;
-                self.score += 1;
-                let _ = self.drop_food();
-            },
+            // the tail moves out of the way, unless the snake is growing.
+            Snake(_) if next_point == self.tail && !growing => {},
+            Snake(_) => return self.die(DeathCause::SelfBite),
+            Wall | Rock => return self.die(DeathCause::Obstacle),
+            Empty | Food => {},
         }
-        self.state
+
+        if !growing {
+            self.move_tail();
+        }
+        events.push(Event::Moved { from: self.head, to: next_point, direction });
+        self.move_head(next_point);
+        if ate {
+            events.push(Event::AteFood(next_point));
+        }
+        if growing {
+            self.pending_growth -= 1;
+            self.snake_len += 1;
+            events.push(Event::Grew(self.snake_len));
+        }
+        if ate {
+            if let Some(food) = self.drop_food() {
+                events.push(Event::FoodSpawned(food));
+            }
+        }
+        if self.field.free_cells() == 0 && !self.field.has_food() {
+            self.state = GameState::Won;
+            events.push(Event::BoardFull);
+        }
+        events
+    }
+
+    fn die(&mut self, cause: DeathCause) -> Vec<Event> {
+        self.state = GameState::GameOver;
+        self.death_cause = Some(cause);
+        vec![Event::Died(cause)]
     }
 
-    fn move_head(&mut self) {
-        let head: Cell = self.field[&self.head].clone();
-        let direction = head.snake_direction().unwrap();
-        let head_next = direction.advance(self.head.clone()).wrap(&self.field.size());
+    fn move_head(&mut self, head_next: Point<isize>) {
+        let direction = self.field[&self.head].snake_direction().unwrap();
         self.field[&head_next] = Cell::Snake(direction);
         self.head = head_next;
-        self.no_turn_back = direction.opposite();
     }
 
     fn move_tail(&mut self) {
-        let tail = self.field[&self.tail].clone();
+        let tail_next = self.field
+            .next_point(self.tail, self.config.topology)
+            .expect("tail follows the body");
         self.field[&self.tail] = Cell::Empty;
-        self.tail = tail.snake_direction().unwrap().advance(self.tail.clone()).wrap(&self.field.size());
+        self.tail = tail_next;
     }
 
-    fn drop_food(&mut self) -> Result<(), ()> {
-        self.field.drop_food(self.snake_len)
+    fn drop_food(&mut self) -> Option<Point<isize>> {
+        self.field.drop_food(&mut self.rng)
     }
 
+    /// queue a turn, to be made on the first tick not taken by earlier turns.
+    /// fails if the game is over, the queue is full, or the snake will already be
+    /// going that way or its opposite by then.
+    #[allow(clippy::result_unit_err)]
     pub fn turn(&mut self, dir: Direction) -> Result<(), ()> {
-        if self.state == GameState::GameOver { return Err(()) }
-        if self.no_turn_back == dir { return Err(()) }
-        match self.field[&self.head] {
-            Cell::Snake(ref mut d) => *d = dir,
-            _ => unreachable!()
-        }
+        if self.is_over() || self.turn_queue.len() >= TURN_QUEUE_LEN { return Err(()) }
+        let heading = self.heading();
+        if dir == heading || dir == heading.opposite() { return Err(()) }
+        self.turn_queue.push_back(dir);
+        self.turns.push((self.ticks, dir));
         Ok(())
     }
 
-    pub fn quit(&mut self) { self.state = GameState::GameOver; }
+    /// direction the snake is moving in, not counting queued turns.
+    pub fn direction(&self) -> Direction {
+        self.field[&self.head].snake_direction().expect("head is on the snake")
+    }
+
+    /// direction the snake will be moving in once all queued turns are made.
+    pub fn heading(&self) -> Direction {
+        self.turn_queue.back().copied().unwrap_or_else(|| self.direction())
+    }
+
+    /// turns waiting for the next ticks, oldest first.
+    pub fn queued_turns(&self) -> impl Iterator<Item = Direction> + '_ {
+        self.turn_queue.iter().copied()
+    }
+
+    pub fn quit(&mut self) {
+        if !self.is_over() {
+            self.state = GameState::GameOver;
+        }
+    }
 
     pub fn state(&self) -> GameState { self.state }
 
+    /// what ended the game, unless it is still on or was quit.
+    pub fn death_cause(&self) -> Option<DeathCause> { self.death_cause }
+
     pub fn pause(&mut self) {
         if matches!(self.state, GameState::GameOn) {
             self.state = GameState::GamePaused;
@@ -122,6 +260,11 @@ impl Game {
         }
     }
 
+    /// whether the game has ended, either lost or won.
+    pub fn is_over(&self) -> bool {
+        matches!(self.state, GameState::GameOver | GameState::Won)
+    }
+
     pub fn is_paused(&self) -> bool {
         matches!(self.state, GameState::GamePaused)
     }
@@ -135,6 +278,27 @@ impl Game {
     pub fn tail(&self) -> Point<isize> { self.tail }
 
     pub fn snake_len(&self) -> usize { self.snake_len }
+
+    /// segments the snake is yet to grow from food it has eaten.
+    pub fn pending_growth(&self) -> usize { self.pending_growth }
+
+    pub fn config(&self) -> &GameConfig { &self.config }
+
+    /// seed this game was started with.
+    pub fn seed(&self) -> u64 { self.seed }
+
+    /// number of steps made so far.
+    pub fn ticks(&self) -> u64 { self.ticks }
+
+    /// successful turns, each with the tick it was made before.
+    pub fn turns(&self) -> &[(u64, Direction)] { &self.turns }
+
+    /// record of the game so far.
+    pub fn replay(&self) -> Replay { Replay::new(self) }
+}
+
+impl Default for Game {
+    fn default() -> Self { Self::new() }
 }
 
 impl fmt::Display for Game {
@@ -165,13 +329,197 @@ mod tests {
         game.turn(Direction::Down).unwrap();
         game.step();
         assert_eq!(game.field[&game.head].snake_direction().unwrap(), Direction::Down);
-        assert_eq!(game.no_turn_back, Direction::Up);
         assert_eq!(game.turn(Direction::Up), Err(()));
+        assert_eq!(game.turn(Direction::Down), Err(()));
+    }
+
+    #[test]
+    fn queued_turns() {
+        let mut game = Game::new();
+        // two quick presses within one tick are made on consecutive ticks.
+        game.turn(Direction::Down).unwrap();
+        game.turn(Direction::Left).unwrap();
+        // checked against the queued turn, not the current direction.
+        assert_eq!(game.turn(Direction::Right), Err(()));
+        game.turn(Direction::Up).unwrap();
+        assert_eq!(game.turn(Direction::Left), Err(()), "queue is full");
+        assert_eq!(game.direction(), Direction::Right);
+        assert_eq!(game.heading(), Direction::Up);
+        for expected in [Direction::Down, Direction::Left, Direction::Up, Direction::Up] {
+            game.step();
+            assert_eq!(game.direction(), expected);
+        }
+        assert_eq!(game.queued_turns().count(), 0);
     }
 
     #[test]
     fn drop_food_test() {
         let mut game = Game::new();
-        game.field.drop_food(game.snake_len).unwrap();
+        game.field.drop_food(&mut game.rng).unwrap();
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let food = |game: &Game| -> Vec<(usize, usize)> {
+            let (w, h) = game.field.size();
+            (0..h).flat_map(|y| (0..w).map(move |x| (x, y)))
+                .filter(|&p| game.field[p] == Food)
+                .collect()
+        };
+        let config = GameConfig::new().size(6, 6).snake_len(2).seed(7);
+        let mut a = Game::with_config(config.clone()).unwrap();
+        let mut b = Game::with_config(config).unwrap();
+        assert_eq!(a.seed(), 7);
+        for i in 0..30 {
+            if i % 6 == 0 {
+                let dir = if i % 12 == 0 { Direction::Down } else { Direction::Right };
+                assert_eq!(a.turn(dir), b.turn(dir));
+            }
+            assert_eq!(a.step(), b.step());
+            assert_eq!(food(&a), food(&b));
+        }
+        assert_eq!(a.score(), b.score());
+    }
+
+    #[test]
+    fn food_avoids_obstacles() {
+        let config = GameConfig::new().size(4, 1).snake_len(1).start(Point { x: 0, y: 0 });
+        for _ in 0..20 {
+            let mut game = Game::with_config(config.clone()).unwrap();
+            for x in 1..4 { game.field[(x, 0)] = Rock }
+            game.field[(2, 0)] = Empty;
+            game.field.drop_food(&mut rand::thread_rng()).unwrap();
+            assert_eq!(game.field[(2, 0)], Food);
+        }
+    }
+
+    #[test]
+    fn play_on_map() {
+        let map = Map::parse("+-----+\n|#  * |\n|>>>  |\n+-----+").unwrap();
+        let mut game = Game::with_config(GameConfig::new().map(map)).unwrap();
+        assert_eq!((game.head(), game.tail(), game.snake_len()), (Point { x: 2, y: 1 }, Point { x: 0, y: 1 }, 3));
+        game.turn(Direction::Up).unwrap();
+        game.step();
+        game.turn(Direction::Right).unwrap();
+        game.step();
+        assert_eq!(game.score(), 1);
+
+        let map = Map::parse("#>>").unwrap();
+        let config = GameConfig::new().map(map);
+        assert_eq!(Game::with_config(config).err().map(|e| e.to_string()),
+                   Some("invalid map: line 1, column 3: snake spawns facing a wall".to_string()));
+    }
+
+    #[test]
+    fn obstacle_kills() {
+        let config = GameConfig::new().size(4, 1).snake_len(1).start(Point { x: 0, y: 0 });
+        let mut game = Game::with_config(config).unwrap();
+        game.field[(1, 0)] = Wall;
+        assert_eq!(game.step(), GameState::GameOver);
+        assert_eq!(game.death_cause(), Some(DeathCause::Obstacle));
+    }
+
+    #[test]
+    fn custom_config() {
+        let config = GameConfig::new().size(8, 6).snake_len(2).direction(Direction::Up);
+        let mut game = Game::with_config(config).unwrap();
+        assert_eq!(game.field().size(), (8, 6));
+        assert_eq!(game.snake_len(), 2);
+        assert_eq!(game.direction(), Direction::Up);
+        assert_eq!(game.tail, Point { x: game.head.x, y: game.head.y + 1 });
+        assert_eq!(game.turn(Direction::Down), Err(()));
+    }
+
+    #[test]
+    fn invalid_config() {
+        let config = GameConfig::new().size(4, 4);
+        assert_eq!(Game::with_config(config).err(), Some(ConfigError::SnakeDoesNotFit));
+    }
+
+    #[test]
+    fn tick_events() {
+        let config = GameConfig::new().size(4, 1).snake_len(2).start(Point { x: 1, y: 0 });
+        let mut game = Game::with_config(config).unwrap();
+        clear_food(&mut game);
+        game.field[(2, 0)] = Food;
+        assert_eq!(game.tick(), vec![
+            Event::Moved { from: Point { x: 1, y: 0 }, to: Point { x: 2, y: 0 }, direction: Direction::Right },
+            Event::AteFood(Point { x: 2, y: 0 }),
+            Event::Grew(3),
+            // the only free cell left.
+            Event::FoodSpawned(Point { x: 3, y: 0 }),
+        ]);
+        assert_eq!(game.tick().last(), Some(&Event::BoardFull));
+        assert_eq!(game.state(), GameState::Won);
+        assert!(game.is_over());
+        assert_eq!(game.tick(), vec![]);
+        assert_eq!(game.turn(Direction::Up), Err(()));
+    }
+
+    #[test]
+    fn win_by_growing() {
+        // last food is eaten while the snake still has room to grow into.
+        let config = GameConfig::new().size(4, 1).snake_len(1).growth(3).start(Point { x: 0, y: 0 });
+        let mut game = Game::with_config(config).unwrap();
+        clear_food(&mut game);
+        game.field[(1, 0)] = Food;
+        game.field[(3, 0)] = Rock;
+        let events = game.tick();
+        assert_eq!(events[events.len() - 1], Event::FoodSpawned(Point { x: 2, y: 0 }));
+        assert_eq!(game.state(), GameState::GameOn);
+        assert_eq!(game.tick().last(), Some(&Event::BoardFull));
+        assert_eq!(game.state(), GameState::Won);
+        game.quit();
+        assert_eq!(game.state(), GameState::Won);
+    }
+
+    #[test]
+    fn self_bite() {
+        let config = GameConfig::new().size(4, 1).snake_len(2).start(Point { x: 1, y: 0 });
+        let mut game = Game::with_config(config).unwrap();
+        game.turn(Direction::Up).unwrap();
+        assert_eq!(game.tick(), vec![Event::Died(DeathCause::SelfBite)]);
+        assert_eq!(game.tick(), vec![]);
+    }
+
+    #[test]
+    fn walled_topology() {
+        let config = GameConfig::new().size(4, 2).snake_len(2).start(Point { x: 2, y: 0 });
+        let mut game = Game::with_config(config.clone().topology(Topology::Walled)).unwrap();
+        game.field[(3, 0)] = Empty;
+        game.step();
+        assert_eq!(game.step(), GameState::GameOver);
+        assert_eq!(game.death_cause(), Some(DeathCause::Wall));
+
+        let mut game = Game::with_config(config).unwrap();
+        game.field[(3, 0)] = Empty;
+        game.field[(0, 0)] = Empty;
+        game.step();
+        assert_eq!(game.step(), GameState::GameOn);
+        assert_eq!(game.head(), Point { x: 0, y: 0 });
+    }
+
+    /// remove randomly dropped food from a single-row field.
+    fn clear_food(game: &mut Game) {
+        for x in 0..game.field.width() {
+            if game.field[(x, 0)] == Food { game.field[(x, 0)] = Empty }
+        }
+    }
+
+    #[test]
+    fn growth_per_food() {
+        let config = GameConfig::new().size(10, 1).snake_len(2).growth(3)
+            .start(Point { x: 1, y: 0 });
+        let mut game = Game::with_config(config).unwrap();
+        clear_food(&mut game);
+        game.field[(2, 0)] = Food;
+        game.step();
+        clear_food(&mut game);
+        assert_eq!((game.score(), game.snake_len()), (1, 3));
+        game.step();
+        game.step();
+        assert_eq!(game.snake_len(), 5);
+        game.step();
+        assert_eq!(game.snake_len(), 5);
     }
 }
@@ -0,0 +1,375 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cell::*;
+use crate::field::*;
+use crate::point::*;
+use crate::topology::*;
+
+
+/// A level loaded from its text representation.
+///
+/// Maps use the same characters as `Display for Field`, so any printed field is a valid map:
+///
+/// ```text
+/// +----------+
+/// |  *       |
+/// |  ####    |
+/// |     ▶▶▶▶ |
+/// |  o       |
+/// +----------+
+/// ```
+///
+/// The border is optional.  Inside it, ` ` is an empty cell, `*` is food, `#` is a wall,
+/// `o` is a rock, and arrows (`▲▼◀▶` or `^v<>`) draw the snake as it spawns, each segment
+/// pointing towards the next one.  There must be exactly one snake.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Map {
+    field: Field,
+    head: Point<isize>,
+    tail: Point<isize>,
+    snake_len: usize,
+    /// line and column of the top left cell in the text.
+    origin: (usize, usize),
+}
+
+impl Map {
+    /// parse map from text.  only the syntax and the shape of the snake are checked here,
+    /// see `validate` for the rest.
+    pub fn parse(text: &str) -> Result<Self, MapError> {
+        let (field, origin) = parse_field(text)?;
+        // report positions relative to the text, not the field.
+        let position = |p: Point<isize>| (origin.0 + p.y as usize, origin.1 + p.x as usize);
+
+        let snake: Vec<Point<isize>> = (0..field.height())
+            .flat_map(|y| (0..field.width()).map(move |x| Point { x: x as isize, y: y as isize }))
+            .filter(|p| field[p].snake_direction().is_some())
+            .collect();
+        if snake.is_empty() {
+            return Err(MapError::new(1, 1, MapErrorKind::NoSpawn));
+        }
+
+        // the tail is the only segment no other segment points at.
+        let targets: Vec<Point<isize>> = snake
+            .iter()
+            .filter_map(|p| field.next_point(*p, Topology::Wrapping))
+            .collect();
+        let mut tails = snake.iter().filter(|p| !targets.contains(p));
+        let tail = match (tails.next(), tails.next()) {
+            (Some(tail), None) => *tail,
+            (_, Some(other)) => {
+                let (line, column) = position(*other);
+                return Err(MapError::new(line, column, MapErrorKind::BrokenSnake));
+            },
+            // every segment is pointed at, i.e. the snake is a loop.
+            (None, None) => {
+                let (line, column) = position(snake[0]);
+                return Err(MapError::new(line, column, MapErrorKind::BrokenSnake));
+            },
+        };
+        // a segment reached twice means the snake runs into itself, with a loop somewhere else.
+        let mut visited = vec![vec![false; field.width()]; field.height()];
+        visited[tail.y as usize][tail.x as usize] = true;
+        let mut head = tail;
+        for _ in 1..snake.len() {
+            let (line, column) = position(head);
+            head = field
+                .next_point(head, Topology::Wrapping)
+                .filter(|p| field[p].snake_direction().is_some())
+                .filter(|p| !visited[p.y as usize][p.x as usize])
+                .ok_or(MapError::new(line, column, MapErrorKind::BrokenSnake))?;
+            visited[head.y as usize][head.x as usize] = true;
+        }
+
+        Ok(Map { field, head, tail, snake_len: snake.len(), origin })
+    }
+
+    /// check that the level is playable on a field with given topology:
+    /// the snake must not cross the edges of a walled field nor face a wall,
+    /// and every free cell must be reachable from its head.
+    pub fn validate(&self, topology: Topology) -> Result<(), MapError> {
+        let size = self.field.size();
+        let mut p = self.tail;
+        while p != self.head {
+            let (line, column) = self.text_position(p);
+            p = self.field
+                .next_point(p, topology)
+                .ok_or(MapError::new(line, column, MapErrorKind::BrokenSnake))?;
+        }
+        let direction = self.field[&self.head].snake_direction().unwrap();
+        let facing = topology.advance(self.head, direction, &size);
+        if facing.is_none_or(|p| self.field[&p].is_obstacle()) {
+            let (line, column) = self.text_position(self.head);
+            return Err(MapError::new(line, column, MapErrorKind::SpawnInWall));
+        }
+        // the head can not face the tail either, which parsing took for a loop.
+        if facing.is_some_and(|p| self.field[&p].snake_direction().is_some()) {
+            let (line, column) = self.text_position(self.head);
+            return Err(MapError::new(line, column, MapErrorKind::SpawnFacingSnake));
+        }
+
+        // flood fill through everything that isn't an obstacle.
+        let mut reached = vec![vec![false; size.0]; size.1];
+        let mut queue = VecDeque::new();
+        reached[self.head.y as usize][self.head.x as usize] = true;
+        queue.push_back(self.head);
+        while let Some(p) = queue.pop_front() {
+            for &dir in &[Up, Down, Left, Right] {
+                if let Some(next) = topology.advance(p, dir, &size) {
+                    let seen = &mut reached[next.y as usize][next.x as usize];
+                    if !*seen && !self.field[&next].is_obstacle() {
+                        *seen = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        for (y, row) in reached.iter().enumerate() {
+            for (x, &seen) in row.iter().enumerate() {
+                if !seen && !self.field[(x, y)].is_obstacle() {
+                    let (line, column) = self.text_position(Point { x: x as isize, y: y as isize });
+                    return Err(MapError::new(line, column, MapErrorKind::Unreachable));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// line and column of a field cell in the text this map was parsed from.
+    fn text_position(&self, p: Point<isize>) -> (usize, usize) {
+        (self.origin.0 + p.y as usize, self.origin.1 + p.x as usize)
+    }
+
+    pub fn field(&self) -> &Field { &self.field }
+
+    pub fn head(&self) -> Point<isize> { self.head }
+
+    pub fn tail(&self) -> Point<isize> { self.tail }
+
+    pub fn snake_len(&self) -> usize { self.snake_len }
+
+    /// direction the snake is heading at the start.
+    pub fn direction(&self) -> Direction {
+        self.field[&self.head].snake_direction().unwrap()
+    }
+}
+
+impl FromStr for Map {
+    type Err = MapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.field.fmt(f)
+    }
+}
+
+/// parse cells of a field drawn as by `Display for Field`, with or without the border.
+/// also returns line and column of the top left cell in the text.
+pub(crate) fn parse_field(text: &str) -> Result<(Field, (usize, usize)), MapError> {
+    let mut lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(n, line)| (n + 1, line))
+        .collect();
+    while lines.last().is_some_and(|(_, line)| line.is_empty()) {
+        lines.pop();
+    }
+
+    // strip the border, if any.
+    let bordered = lines.first().is_some_and(|(_, line)| line.starts_with('+'));
+    if bordered {
+        let (top, bottom) = (lines[0], lines[lines.len() - 1]);
+        if lines.len() < 2 || !is_h_border(bottom.1) {
+            return Err(MapError::new(bottom.0, 1, MapErrorKind::BadBorder));
+        }
+        if !is_h_border(top.1) {
+            return Err(MapError::new(top.0, 1, MapErrorKind::BadBorder));
+        }
+        lines = lines[1..lines.len() - 1].to_vec();
+    }
+    let col_offset = if bordered { 2 } else { 1 };
+
+    let mut rows: Vec<(usize, Vec<char>)> = Vec::with_capacity(lines.len());
+    for (n, line) in lines {
+        let mut chars: Vec<char> = line.chars().collect();
+        if bordered {
+            if chars.first() != Some(&'|') {
+                return Err(MapError::new(n, 1, MapErrorKind::BadBorder));
+            }
+            if chars.len() < 2 || chars.last() != Some(&'|') {
+                return Err(MapError::new(n, chars.len().max(1), MapErrorKind::BadBorder));
+            }
+            chars = chars[1..chars.len() - 1].to_vec();
+        }
+        rows.push((n, chars));
+    }
+
+    let width = rows.first().map_or(0, |(_, row)| row.len());
+    if width == 0 {
+        return Err(MapError::new(1, 1, MapErrorKind::Empty));
+    }
+    let mut field = Field::with_size(width, rows.len());
+    for (y, (n, row)) in rows.iter().enumerate() {
+        if row.len() != width {
+            let column = row.len().min(width) + col_offset;
+            return Err(MapError::new(*n, column, MapErrorKind::RaggedRow));
+        }
+        for (x, &c) in row.iter().enumerate() {
+            field[(x, y)] = match c {
+                ' ' => Empty,
+                '*' => Food,
+                '#' => Wall,
+                'o' => Rock,
+                '▲' | '^' => Snake(Up),
+                '▼' | 'v' => Snake(Down),
+                '◀' | '<' => Snake(Left),
+                '▶' | '>' => Snake(Right),
+                c => return Err(MapError::new(*n, x + col_offset, MapErrorKind::UnexpectedChar(c))),
+            };
+        }
+    }
+    Ok((field, (rows[0].0, col_offset)))
+}
+
+fn is_h_border(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().collect();
+    chars.len() >= 2
+        && chars[0] == '+'
+        && chars[chars.len() - 1] == '+'
+        && chars[1..chars.len() - 1].iter().all(|&c| c == '-')
+}
+
+/// Problem found in a map, with 1-based position in its text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: MapErrorKind,
+}
+
+impl MapError {
+    fn new(line: usize, column: usize, kind: MapErrorKind) -> Self {
+        MapError { line, column, kind }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapErrorKind {
+    /// map has no cells.
+    Empty,
+    /// border lines are malformed or missing.
+    BadBorder,
+    /// row length differs from the first row.
+    RaggedRow,
+    /// character is not a known cell.
+    UnexpectedChar(char),
+    /// map has no snake to spawn.
+    NoSpawn,
+    /// arrows don't form a single snake.
+    BrokenSnake,
+    /// snake spawns facing a wall.
+    SpawnInWall,
+    /// snake spawns facing its own body.
+    SpawnFacingSnake,
+    /// free cell which the snake can never get to.
+    Unreachable,
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl fmt::Display for MapErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MapErrorKind::Empty => write!(f, "map is empty"),
+            MapErrorKind::BadBorder => write!(f, "malformed border"),
+            MapErrorKind::RaggedRow => write!(f, "row length differs from the first row"),
+            MapErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            MapErrorKind::NoSpawn => write!(f, "no snake spawn on the map"),
+            MapErrorKind::BrokenSnake => write!(f, "snake arrows do not form a single snake"),
+            MapErrorKind::SpawnInWall => write!(f, "snake spawns facing a wall"),
+            MapErrorKind::SpawnFacingSnake => write!(f, "snake spawns facing its own body"),
+            MapErrorKind::Unreachable => write!(f, "cell is unreachable from the spawn"),
+        }
+    }
+}
+
+impl Error for MapError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_bordered() {
+        let map: Map = "+-----+\n\
+                        |* #  |\n\
+                        | >>v |\n\
+                        |o  v |\n\
+                        +-----+\n".parse().unwrap();
+        assert_eq!(map.field().size(), (5, 3));
+        assert_eq!(map.tail(), Point { x: 1, y: 1 });
+        assert_eq!(map.head(), Point { x: 3, y: 2 });
+        assert_eq!(map.snake_len(), 4);
+        assert_eq!(map.direction(), Down);
+        assert_eq!(map.field()[(0, 0)], Food);
+        assert_eq!(map.field()[(2, 0)], Wall);
+        assert_eq!(map.field()[(0, 2)], Rock);
+        assert_eq!(map.validate(Topology::Wrapping), Ok(()));
+    }
+
+    #[test]
+    fn field_display_round_trip() {
+        let mut field = Field::with_size(6, 2);
+        field.init_snake(Point { x: 3, y: 1 }, Right, 3);
+        field[(5, 0)] = Wall;
+        let map = Map::parse(&field.to_string()).unwrap();
+        assert_eq!(map.field(), &field);
+        assert_eq!(map.to_string(), field.to_string());
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let err = |text: &str| Map::parse(text).unwrap_err();
+        assert_eq!(err(""), MapError::new(1, 1, MapErrorKind::Empty));
+        assert_eq!(err("  >\n x "), MapError::new(2, 2, MapErrorKind::UnexpectedChar('x')));
+        assert_eq!(err(" >\n   "), MapError::new(2, 3, MapErrorKind::RaggedRow));
+        assert_eq!(err("+--+\n|> |\n|  \n+--+"), MapError::new(3, 3, MapErrorKind::BadBorder));
+        assert_eq!(err(" * "), MapError::new(1, 1, MapErrorKind::NoSpawn));
+        assert_eq!(err("> > "), MapError::new(1, 3, MapErrorKind::BrokenSnake));
+        assert_eq!(err("><"), MapError::new(1, 1, MapErrorKind::BrokenSnake));
+        assert_eq!(err(">>v><\n ^<  "), MapError::new(2, 2, MapErrorKind::BrokenSnake));
+    }
+
+    #[test]
+    fn validation_errors() {
+        let map = Map::parse("+---+\n|>>#|\n|   |\n+---+").unwrap();
+        assert_eq!(map.validate(Topology::Wrapping), Err(MapError::new(2, 3, MapErrorKind::SpawnInWall)));
+
+        let map = Map::parse(" >>v\n>^<<").unwrap();
+        assert_eq!(map.validate(Topology::Wrapping), Err(MapError::new(2, 3, MapErrorKind::SpawnFacingSnake)));
+
+        let map = Map::parse("  >").unwrap();
+        assert_eq!(map.validate(Topology::Wrapping), Ok(()));
+        assert_eq!(map.validate(Topology::Walled), Err(MapError::new(1, 3, MapErrorKind::SpawnInWall)));
+
+        // snake wraps across the edge.
+        let map = Map::parse(">  >").unwrap();
+        assert_eq!(map.validate(Topology::Wrapping), Ok(()));
+        assert_eq!(map.validate(Topology::Walled), Err(MapError::new(1, 4, MapErrorKind::BrokenSnake)));
+
+        let map = Map::parse("+-----+\n|>  # |\n|   #*|\n+-----+").unwrap();
+        assert_eq!(map.validate(Topology::Wrapping), Ok(()));
+        assert_eq!(map.validate(Topology::Walled), Err(MapError::new(2, 6, MapErrorKind::Unreachable)));
+    }
+}
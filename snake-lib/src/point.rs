@@ -14,6 +14,8 @@ impl<T> PartialEq for Point<T> where T: NumCast + PartialEq {
     }
 }
 
+impl<T> Eq for Point<T> where T: NumCast + Eq {}
+
 impl<T: NumCast> From<(T, T)> for Point<T> {
     fn from(tuple: (T, T)) -> Self {
         Point { x: tuple.0, y: tuple.1 }
@@ -45,6 +47,12 @@ impl<T: NumCast> Point<T> {
             y: cast(modulus!(self.y.to_isize().unwrap(), around.height())).unwrap(),
         }
     }
+
+    /// whether point lies within `(0, 0)..(width, height)` rectangle.
+    pub fn is_inside(&self, area: &dyn Size2D<usize>) -> bool {
+        let (x, y) = (self.x.to_isize().unwrap(), self.y.to_isize().unwrap());
+        0 <= x && x < area.width() as isize && 0 <= y && y < area.height() as isize
+    }
 }
 
 #[cfg(test)]
@@ -55,7 +63,7 @@ mod test {
         let p = Point { x: 4, y: 6 };
         let rect = (2usize, 4usize);
         assert_eq!(p.wrap(&rect), Point { x: 0, y: 2 });
-        assert_eq!(Direction::Right.advance(p.clone()).wrap(&rect), Point { x: 1, y: 2 });
+        assert_eq!(Direction::Right.advance(p).wrap(&rect), Point { x: 1, y: 2 });
     }
 
     #[test]
@@ -64,4 +72,13 @@ mod test {
             Point::from((-1, -2)).wrap(&(4, 3)),
             Point::from((3, 1)));
     }
+
+    #[test]
+    fn test_is_inside() {
+        let rect = (3usize, 2usize);
+        assert!(Point::from((0, 0)).is_inside(&rect));
+        assert!(Point::from((2, 1)).is_inside(&rect));
+        assert!(!Point::from((3, 1)).is_inside(&rect));
+        assert!(!Point::from((0, -1)).is_inside(&rect));
+    }
 }
@@ -0,0 +1,164 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cell::*;
+use crate::config::*;
+use crate::serial::*;
+use crate::{Game, GameState};
+
+
+const VERSION: u32 = 2;
+
+/// Record of a game: its configuration, seed, and every turn made along the way.
+///
+/// Since food placement is deterministic for a given seed,
+/// this is enough to re-simulate the game exactly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    config: GameConfig,
+    turns: Vec<(u64, Direction)>,
+    ticks: u64,
+}
+
+impl Replay {
+    /// record of the game so far.
+    pub fn new(game: &Game) -> Self {
+        Replay {
+            config: game.config().clone().seed(game.seed()),
+            turns: game.turns().to_vec(),
+            ticks: game.ticks(),
+        }
+    }
+
+    /// configuration of the recorded game, with seed always set.
+    pub fn config(&self) -> &GameConfig { &self.config }
+
+    /// successful turns, each with the tick it was made before.
+    pub fn turns(&self) -> &[(u64, Direction)] { &self.turns }
+
+    /// total number of ticks in the recorded game.
+    pub fn ticks(&self) -> u64 { self.ticks }
+
+    /// game in its initial state, together with a player to re-simulate it tick by tick.
+    pub fn play(&self) -> Result<(Game, ReplayPlayer), ConfigError> {
+        let game = Game::with_config(self.config.clone())?;
+        Ok((game, ReplayPlayer { replay: self.clone(), next_turn: 0 }))
+    }
+
+    /// re-simulate the whole game and return it in its final state.
+    pub fn simulate(&self) -> Result<Game, ConfigError> {
+        let (mut game, mut player) = self.play()?;
+        while !player.is_finished(&game) {
+            player.step(&mut game);
+        }
+        Ok(game)
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_header(f, "snake-replay", VERSION)?;
+        write_config(f, &self.config)?;
+        writeln!(f, "turns {}", self.turns.len())?;
+        for &(tick, direction) in &self.turns {
+            writeln!(f, "{} {}", tick, direction_name(direction))?;
+        }
+        writeln!(f, "ticks {}", self.ticks)
+    }
+}
+
+impl FromStr for Replay {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut reader = Reader::new(s);
+        read_header(&mut reader, "snake-replay", VERSION)?;
+        let config = read_config(&mut reader)?;
+        if config.seed.is_none() {
+            return reader.error("replay has no seed");
+        }
+        let n: usize = reader.number("turns")?;
+        let mut turns = Vec::with_capacity(n);
+        for _ in 0..n {
+            let line = reader.raw_line()?;
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [tick, direction] => turns.push((reader.parse(tick)?, reader.direction(direction)?)),
+                _ => return reader.error("expected tick and direction"),
+            }
+        }
+        let ticks = reader.number("ticks")?;
+        reader.end()?;
+        Ok(Replay { config, turns, ticks })
+    }
+}
+
+/// Feeds recorded turns into a game.
+pub struct ReplayPlayer {
+    replay: Replay,
+    next_turn: usize,
+}
+
+impl ReplayPlayer {
+    /// make the turns recorded for the current tick and step the game.
+    /// does nothing once the replay is over.
+    pub fn step(&mut self, game: &mut Game) -> GameState {
+        if self.is_finished(game) { return game.state() }
+        while let Some(&(tick, direction)) = self.replay.turns.get(self.next_turn) {
+            if tick > game.ticks() { break }
+            let _ = game.turn(direction);
+            self.next_turn += 1;
+        }
+        game.step()
+    }
+
+    /// whether all recorded ticks have been played or the game has ended.
+    pub fn is_finished(&self, game: &Game) -> bool {
+        game.ticks() >= self.replay.ticks || game.is_over()
+    }
+
+    pub fn replay(&self) -> &Replay { &self.replay }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn play_some(game: &mut Game) {
+        for i in 0..40 {
+            if i % 7 == 3 { let _ = game.turn(Down); }
+            if i % 7 == 5 { let _ = game.turn(Right); }
+            game.step();
+        }
+    }
+
+    #[test]
+    fn replay_reproduces_game() {
+        let mut game = Game::with_config(GameConfig::new().size(12, 8)).unwrap();
+        play_some(&mut game);
+        let replay = Replay::new(&game);
+        let copy = replay.simulate().unwrap();
+        assert_eq!(copy.field(), game.field());
+        assert_eq!((copy.score(), copy.ticks(), copy.state()), (game.score(), game.ticks(), game.state()));
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let map = crate::Map::parse("+----+\n|>>  |\n| #  |\n+----+\n").unwrap();
+        let config = GameConfig::new().map(map).topology(crate::Topology::Walled).growth(2).seed(99);
+        let mut game = Game::with_config(config).unwrap();
+        play_some(&mut game);
+        let replay = Replay::new(&game);
+        let text = replay.to_string();
+        assert_eq!(text.parse::<Replay>(), Ok(replay));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |text: &str| text.parse::<Replay>().unwrap_err().to_string();
+        assert_eq!(err("snake-replay 1\n"), "line 1: unsupported snake-replay version 1");
+        assert_eq!(err("snake-replay 2\nsize 5 5\nsnake 2 up\nend\nturns 0\nticks 0\n"),
+                   "line 4: replay has no seed");
+        assert_eq!(err("snake-replay 2\nsize 5 5\nsnake 2 up\nseed 1\nend\nturns 1\n0 north\n"),
+                   "line 7: invalid direction \"north\"");
+    }
+}
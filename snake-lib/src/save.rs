@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+
+use crate::map::parse_field;
+use crate::point::*;
+use crate::rng::*;
+use crate::serial::*;
+use crate::{DeathCause, Game, GameState, TURN_QUEUE_LEN};
+
+
+const VERSION: u32 = 2;
+
+impl Game {
+    /// serialize complete state of the game, so it can be resumed later with `Game::restore`.
+    pub fn save(&self) -> String {
+        struct Save<'a>(&'a Game);
+
+        impl<'a> std::fmt::Display for Save<'a> {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let game = self.0;
+                write_header(f, "snake-save", VERSION)?;
+                write_config(f, &game.config)?;
+                writeln!(f, "seed {}", game.seed)?;
+                writeln!(f, "rng {}", game.rng.state())?;
+                writeln!(f, "state {}", match game.state {
+                    GameState::GameOn => "on",
+                    GameState::GamePaused => "paused",
+                    GameState::GameOver => "over",
+                    GameState::Won => "won",
+                })?;
+                if let Some(cause) = game.death_cause {
+                    writeln!(f, "death {}", death_cause_name(cause))?;
+                }
+                writeln!(f, "score {}", game.score)?;
+                writeln!(f, "snake-len {}", game.snake_len)?;
+                writeln!(f, "pending-growth {}", game.pending_growth)?;
+                write!(f, "queue")?;
+                for direction in game.queued_turns() {
+                    write!(f, " {}", direction_name(direction))?;
+                }
+                writeln!(f)?;
+                writeln!(f, "head {} {}", game.head.x, game.head.y)?;
+                writeln!(f, "tail {} {}", game.tail.x, game.tail.y)?;
+                writeln!(f, "ticks {}", game.ticks)?;
+                writeln!(f, "turns {}", game.turns.len())?;
+                for &(tick, direction) in &game.turns {
+                    writeln!(f, "{} {}", tick, direction_name(direction))?;
+                }
+                writeln!(f, "field {}", game.field.height() + 2)?;
+                write!(f, "{}", game.field)
+            }
+        }
+
+        Save(self).to_string()
+    }
+
+    /// resume a game saved with `Game::save`.
+    pub fn restore(text: &str) -> Result<Game, ParseError> {
+        let mut reader = Reader::new(text);
+        read_header(&mut reader, "snake-save", VERSION)?;
+        let config = read_config(&mut reader)?;
+        let seed = reader.number("seed")?;
+        let rng = GameRng::new(reader.number("rng")?);
+        let [state] = reader.values("state")?;
+        let state = match state {
+            "on" => GameState::GameOn,
+            "paused" => GameState::GamePaused,
+            "over" => GameState::GameOver,
+            "won" => GameState::Won,
+            _ => return reader.error(format!("invalid state {:?}", state)),
+        };
+        let mut death_cause = None;
+        let mut line = reader.expect_any()?;
+        if let ["death", cause] = line[..] {
+            death_cause = Some(match cause {
+                "self-bite" => DeathCause::SelfBite,
+                "wall" => DeathCause::Wall,
+                "obstacle" => DeathCause::Obstacle,
+                _ => return reader.error(format!("invalid death cause {:?}", cause)),
+            });
+            line = reader.expect_any()?;
+        }
+        let score = match line[..] {
+            ["score", score] => reader.parse(score)?,
+            _ => return reader.error("expected \"score\""),
+        };
+        let snake_len = reader.number("snake-len")?;
+        let pending_growth = reader.number("pending-growth")?;
+        let turn_queue = reader.expect("queue")?
+            .into_iter()
+            .map(|d| reader.direction(d))
+            .collect::<Result<VecDeque<_>, _>>()?;
+        if turn_queue.len() > TURN_QUEUE_LEN {
+            return reader.error("too many queued turns");
+        }
+        let [x, y] = reader.values("head")?;
+        let head: Point<isize> = Point { x: reader.parse(x)?, y: reader.parse(y)? };
+        let [x, y] = reader.values("tail")?;
+        let tail: Point<isize> = Point { x: reader.parse(x)?, y: reader.parse(y)? };
+        let ticks = reader.number("ticks")?;
+        let n: usize = reader.number("turns")?;
+        let mut turns = Vec::with_capacity(n);
+        for _ in 0..n {
+            let line = reader.raw_line()?;
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [tick, direction] => turns.push((reader.parse(tick)?, reader.direction(direction)?)),
+                _ => return reader.error("expected tick and direction"),
+            }
+        }
+        let n = reader.number("field")?;
+        let (first, text) = reader.block(n)?;
+        let (field, _) = parse_field(&text).map_err(|e| map_error(first, e))?;
+        reader.end()?;
+
+        // the game goes by the config for the size of the field.
+        let (width, height) = field.size();
+        if (width, height) != (config.width, config.height) {
+            return reader.error(format!("field is {}x{}, but size is {}x{}", width, height, config.width, config.height));
+        }
+
+        // make sure the snake can be followed from its tail to its head, through as many
+        // segments as it is long, and that nothing else on the field is snake.
+        let size = field.size();
+        let is_snake = |p: &Point<isize>| p.is_inside(&size) && field[p].snake_direction().is_some();
+        if !is_snake(&head) || !is_snake(&tail) {
+            return reader.error("head and tail must be on the snake");
+        }
+        let broken = format!("snake of length {} does not lead from tail to head", snake_len);
+        let mut visited = vec![vec![false; size.0]; size.1];
+        visited[tail.y as usize][tail.x as usize] = true;
+        let mut p = tail;
+        for _ in 1..snake_len {
+            match field.next_point(p, config.topology) {
+                Some(next) if is_snake(&next) && !visited[next.y as usize][next.x as usize] => {
+                    visited[next.y as usize][next.x as usize] = true;
+                    p = next;
+                },
+                _ => return reader.error(broken),
+            }
+        }
+        if p != head {
+            return reader.error(broken);
+        }
+        let cells = field.rows().iter().flatten().filter(|cell| cell.snake_direction().is_some()).count();
+        if cells != snake_len {
+            return reader.error(format!("{} snake cells for a snake of length {}", cells, snake_len));
+        }
+        config.validate().or_else(|e| reader.error(e))?;
+
+        Ok(Game {
+            field,
+            tail,
+            head,
+            score,
+            snake_len,
+            state,
+            death_cause,
+            turn_queue,
+            pending_growth,
+            config,
+            seed,
+            rng,
+            ticks,
+            turns,
+        })
+    }
+}
+
+fn death_cause_name(cause: DeathCause) -> &'static str {
+    match cause {
+        DeathCause::SelfBite => "self-bite",
+        DeathCause::Wall => "wall",
+        DeathCause::Obstacle => "obstacle",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn save_and_restore() {
+        let mut game = Game::with_config(GameConfig::new().size(10, 6).topology(Topology::Walled)).unwrap();
+        for i in 0..12 {
+            if i == 2 { game.turn(Direction::Down).unwrap(); }
+            if i == 4 { game.turn(Direction::Left).unwrap(); }
+            game.step();
+        }
+        game.turn(Direction::Up).unwrap();
+        game.pause();
+        let text = game.save();
+        let mut copy = Game::restore(&text).unwrap();
+        assert_eq!(copy.save(), text);
+        assert!(copy.is_paused());
+
+        // both games continue identically, including food placement.
+        game.unpause();
+        copy.unpause();
+        for _ in 0..20 {
+            assert_eq!(game.step(), copy.step());
+            assert_eq!(game.field(), copy.field());
+        }
+        assert_eq!(game.replay(), copy.replay());
+    }
+
+    #[test]
+    fn reject_corrupted() {
+        let game = Game::with_seed(3);
+        let text = game.save().replace("snake-len 5", "snake-len 4");
+        assert_eq!(Game::restore(&text).err().map(|e| e.message),
+                   Some("snake of length 4 does not lead from tail to head".to_string()));
+        let text = game.save().replace("snake-len 5", "snake-len 9");
+        assert_eq!(Game::restore(&text).err().map(|e| e.message),
+                   Some("snake of length 9 does not lead from tail to head".to_string()));
+        assert!(Game::restore("snake-save 7\n").is_err());
+
+        let text = game.save().replace("size 20 10", "size 20 12");
+        assert_eq!(Game::restore(&text).err().map(|e| e.message),
+                   Some("field is 20x10, but size is 20x12".to_string()));
+    }
+}
@@ -0,0 +1,177 @@
+//! Best games played, kept in a table per kind of board.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::config::*;
+use crate::serial::*;
+use crate::topology::*;
+
+
+const VERSION: u32 = 1;
+
+/// most games a table keeps.
+pub const HIGH_SCORES_LEN: usize = 10;
+
+/// Finished game worth remembering.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: usize,
+    /// length of the snake at the end.
+    pub length: usize,
+    /// time spent playing, not counting pauses.
+    pub duration: Duration,
+    /// when the game ended, in seconds since the Unix epoch.
+    pub date: u64,
+}
+
+/// Best games played on one kind of board, best first.
+/// of equal scores, the one set first stays ahead.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScoreTable {
+    entries: Vec<HighScore>,
+}
+
+impl ScoreTable {
+    pub fn new() -> Self {
+        ScoreTable { entries: Vec::new() }
+    }
+
+    pub fn entries(&self) -> &[HighScore] { &self.entries }
+
+    /// place a game with given score would take, if it makes it into the table.
+    pub fn rank(&self, score: usize) -> Option<usize> {
+        let rank = self.entries.iter().take_while(|entry| entry.score >= score).count();
+        Some(rank).filter(|&rank| score > 0 && rank < HIGH_SCORES_LEN)
+    }
+
+    /// add a game, pushing the last one out if the table is full.
+    /// returns its place, or `None` if it did not make it.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self.rank(entry.score)?;
+        self.entries.insert(rank, entry);
+        self.entries.truncate(HIGH_SCORES_LEN);
+        Some(rank)
+    }
+}
+
+/// name of the table for games with given config: boards of the same size and topology
+/// share one, while each map gets its own.
+pub fn board_name(config: &GameConfig) -> String {
+    let topology = match config.topology {
+        Topology::Wrapping => "wrapping",
+        Topology::Walled => "walled",
+    };
+    let mut name = format!("{}x{}-{}", config.width, config.height, topology);
+    if let Some(ref map) = config.map {
+        name.push_str(&format!("-map-{:08x}", fnv1a(map.to_string().as_bytes())));
+    }
+    name
+}
+
+/// 32-bit FNV-1a hash, which unlike `std::hash` is sure to stay the same between builds.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+}
+
+impl fmt::Display for ScoreTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_header(f, "snake-scores", VERSION)?;
+        writeln!(f, "scores {}", self.entries.len())?;
+        for entry in &self.entries {
+            // name goes last, as it may contain spaces.
+            writeln!(f, "{} {} {} {} {}",
+                     entry.date, entry.score, entry.length, entry.duration.as_secs(), entry.name)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ScoreTable {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut reader = Reader::new(s);
+        read_header(&mut reader, "snake-scores", VERSION)?;
+        let n: usize = reader.number("scores")?;
+        if n > HIGH_SCORES_LEN {
+            return reader.error(format!("more than {} scores", HIGH_SCORES_LEN));
+        }
+        let mut entries = Vec::with_capacity(n);
+        for _ in 0..n {
+            let line = reader.raw_line()?;
+            let mut words = line.splitn(5, ' ');
+            let mut number = || reader.parse::<u64>(words.next().unwrap_or(""));
+            let (date, score, length, seconds) = (number()?, number()?, number()?, number()?);
+            let name = words.next().unwrap_or("").to_string();
+            entries.push(HighScore {
+                name,
+                score: score as usize,
+                length: length as usize,
+                duration: Duration::from_secs(seconds),
+                date,
+            });
+        }
+        if entries.windows(2).any(|pair| pair[0].score < pair[1].score) {
+            return reader.error("scores are out of order");
+        }
+        reader.end()?;
+        Ok(ScoreTable { entries })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(name: &str, score: usize) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            length: score + 5,
+            duration: Duration::from_secs(42),
+            date: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn ranking() {
+        let mut table = ScoreTable::new();
+        assert_eq!(table.rank(0), None);
+        assert_eq!(table.insert(entry("first", 3)), Some(0));
+        assert_eq!(table.insert(entry("better", 7)), Some(0));
+        // ties go below
+        assert_eq!(table.insert(entry("tie", 3)), Some(2));
+        for i in 0..HIGH_SCORES_LEN {
+            table.insert(entry("filler", 5 + i));
+        }
+        assert_eq!(table.entries().len(), HIGH_SCORES_LEN);
+        assert_eq!(table.rank(3), None);
+        assert_eq!(table.insert(entry("late", 4)), None);
+        assert!(table.entries().iter().all(|entry| entry.score >= 5));
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut table = ScoreTable::new();
+        table.insert(entry("Jane Doe", 12));
+        table.insert(entry("", 4));
+        let text = table.to_string();
+        assert_eq!(text.parse::<ScoreTable>(), Ok(table));
+
+        let bad = text.replace("scores 2", "scores 3");
+        assert!(bad.parse::<ScoreTable>().is_err());
+        let swapped = text.replacen(" 12 ", " 1 ", 1);
+        assert!(swapped.parse::<ScoreTable>().is_err());
+    }
+
+    #[test]
+    fn boards() {
+        let config = GameConfig::new().size(20, 10);
+        assert_eq!(board_name(&config), "20x10-wrapping");
+        let walled = config.clone().topology(Topology::Walled);
+        assert_eq!(board_name(&walled), "20x10-walled");
+    }
+}
@@ -0,0 +1,108 @@
+//! Shapes of the parts of the snake, so it can be drawn as one connected line.
+
+use crate::cell::*;
+use crate::point::*;
+use crate::Game;
+
+
+/// Part of the snake, by how it connects to the parts next to it.
+///
+/// the direction of a part is the one the snake went in when leaving it,
+/// towards the part in front of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// front of the snake, moving in given direction.
+    Head(Direction),
+    /// part of the body going straight on.
+    Straight(Direction),
+    /// part of the body where the snake turned, coming in going `from` and leaving going `to`.
+    Corner { from: Direction, to: Direction },
+    /// end of the snake.
+    Tail(Direction),
+}
+
+impl Segment {
+    /// part of the body, from the direction of the part behind it, which leads into it,
+    /// and its own, which leads to the part in front of it.
+    pub fn body(incoming: Direction, outgoing: Direction) -> Segment {
+        if incoming == outgoing {
+            Segment::Straight(outgoing)
+        } else {
+            Segment::Corner { from: incoming, to: outgoing }
+        }
+    }
+
+    /// sides of its cell through which the part connects to the parts behind and in front of it.
+    /// the head has nothing in front, and the tail nothing behind.
+    pub fn sides(self) -> (Option<Direction>, Option<Direction>) {
+        match self {
+            Segment::Head(dir) => (Some(dir.opposite()), None),
+            Segment::Straight(dir) => (Some(dir.opposite()), Some(dir)),
+            Segment::Corner { from, to } => (Some(from.opposite()), Some(to)),
+            Segment::Tail(dir) => (None, Some(dir)),
+        }
+    }
+}
+
+impl Game {
+    /// every part of the snake with its shape, from the head back to the tail.
+    pub fn segments(&self) -> Vec<(Point<isize>, Segment)> {
+        let topology = self.config.topology;
+        let mut points = vec![self.tail];
+        let mut p = self.tail;
+        while p != self.head {
+            p = self.field.next_point(p, topology).expect("snake is connected");
+            points.push(p);
+        }
+        let direction = |p: &Point<isize>| self.field[p].snake_direction().expect("snake is connected");
+        let last = points.len() - 1;
+        let mut segments: Vec<_> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let segment = if i == last {
+                    Segment::Head(direction(p))
+                } else if i == 0 {
+                    Segment::Tail(direction(p))
+                } else {
+                    Segment::body(direction(&points[i - 1]), direction(p))
+                };
+                (*p, segment)
+            })
+            .collect();
+        segments.reverse();
+        segments
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::GameConfig;
+
+    #[test]
+    fn turning_snake() {
+        let mut game = Game::with_config(GameConfig::new().size(10, 10).snake_len(4).direction(Right)).unwrap();
+        // food dropped right where the snake turns would make it grow.
+        let below = Point { x: game.head().x, y: game.head().y + 1 };
+        game.field[&below] = Empty;
+        game.turn(Down).unwrap();
+        game.step();
+        let shapes: Vec<Segment> = game.segments().into_iter().map(|(_, segment)| segment).collect();
+        assert_eq!(shapes, [
+            Segment::Head(Down),
+            Segment::Corner { from: Right, to: Down },
+            Segment::Straight(Right),
+            Segment::Tail(Right),
+        ]);
+        let (head, _) = game.segments()[0];
+        assert_eq!(head, game.head());
+    }
+
+    #[test]
+    fn sides() {
+        assert_eq!(Segment::body(Right, Down).sides(), (Some(Left), Some(Down)));
+        assert_eq!(Segment::Head(Up).sides(), (Some(Down), None));
+        assert_eq!(Segment::Tail(Left).sides(), (None, Some(Left)));
+    }
+}
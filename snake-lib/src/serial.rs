@@ -0,0 +1,208 @@
+//! Plain text serialization shared by replay and save files.
+//!
+//! Files are made of lines with a keyword followed by space separated values.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cell::*;
+use crate::config::*;
+use crate::map::*;
+use crate::point::*;
+use crate::topology::*;
+
+
+/// Problem found while reading a file, with 1-based line number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Line by line reader which keeps track of line numbers.
+pub(crate) struct Reader<'a> {
+    lines: std::str::Lines<'a>,
+    line: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Reader { lines: text.lines(), line: 0 }
+    }
+
+    /// error at the current line.
+    pub fn error<T, M: ToString>(&self, message: M) -> Result<T, ParseError> {
+        Err(ParseError { line: self.line, message: message.to_string() })
+    }
+
+    /// next raw line.
+    pub fn raw_line(&mut self) -> Result<&'a str, ParseError> {
+        self.line += 1;
+        match self.lines.next() {
+            Some(line) => Ok(line),
+            None => self.error("unexpected end of file"),
+        }
+    }
+
+    /// next line, which must start with given keyword.  returns the values that follow it.
+    pub fn expect(&mut self, keyword: &str) -> Result<Vec<&'a str>, ParseError> {
+        let line = self.raw_line()?;
+        let mut words = line.split_whitespace();
+        if words.next() != Some(keyword) {
+            return self.error(format!("expected {:?}", keyword));
+        }
+        Ok(words.collect())
+    }
+
+    /// next line split into words, whatever its keyword is.
+    pub fn expect_any(&mut self) -> Result<Vec<&'a str>, ParseError> {
+        Ok(self.raw_line()?.split_whitespace().collect())
+    }
+
+    /// like `expect`, for keywords followed by exactly `N` values.
+    pub fn values<const N: usize>(&mut self, keyword: &str) -> Result<[&'a str; N], ParseError> {
+        let values = self.expect(keyword)?;
+        match <[&str; N]>::try_from(values) {
+            Ok(values) => Ok(values),
+            Err(_) => self.error(format!("{:?} takes {} values", keyword, N)),
+        }
+    }
+
+    /// keyword followed by a single number.
+    pub fn number<T: FromStr>(&mut self, keyword: &str) -> Result<T, ParseError> {
+        let [value] = self.values(keyword)?;
+        self.parse(value)
+    }
+
+    pub fn parse<T: FromStr>(&self, value: &str) -> Result<T, ParseError> {
+        match value.parse() {
+            Ok(value) => Ok(value),
+            Err(_) => self.error(format!("invalid value {:?}", value)),
+        }
+    }
+
+    pub fn direction(&self, value: &str) -> Result<Direction, ParseError> {
+        match value {
+            "up" => Ok(Up),
+            "down" => Ok(Down),
+            "left" => Ok(Left),
+            "right" => Ok(Right),
+            _ => self.error(format!("invalid direction {:?}", value)),
+        }
+    }
+
+    /// next `n` lines as they are.  also returns the number of the first one.
+    pub fn block(&mut self, n: usize) -> Result<(usize, String), ParseError> {
+        let first = self.line + 1;
+        let mut text = String::new();
+        for _ in 0..n {
+            text.push_str(self.raw_line()?);
+            text.push('\n');
+        }
+        Ok((first, text))
+    }
+
+    /// check that nothing but blank lines is left.
+    pub fn end(&mut self) -> Result<(), ParseError> {
+        for line in &mut self.lines {
+            self.line += 1;
+            if !line.trim().is_empty() {
+                return self.error("unexpected data after the end");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// error in a map embedded in a file, starting at given line.
+pub(crate) fn map_error(first: usize, error: MapError) -> ParseError {
+    ParseError {
+        line: first + error.line - 1,
+        message: format!("column {}: {}", error.column, error.kind),
+    }
+}
+
+pub(crate) fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Up => "up",
+        Down => "down",
+        Left => "left",
+        Right => "right",
+    }
+}
+
+/// header line identifying file kind and format version.
+pub(crate) fn write_header(f: &mut fmt::Formatter, kind: &str, version: u32) -> fmt::Result {
+    writeln!(f, "{} {}", kind, version)
+}
+
+pub(crate) fn read_header(reader: &mut Reader, kind: &str, version: u32) -> Result<(), ParseError> {
+    let [v] = reader.values(kind)?;
+    let v: u32 = reader.parse(v)?;
+    if v != version {
+        return reader.error(format!("unsupported {} version {}", kind, v));
+    }
+    Ok(())
+}
+
+pub(crate) fn write_config(f: &mut fmt::Formatter, config: &GameConfig) -> fmt::Result {
+    writeln!(f, "size {} {}", config.width, config.height)?;
+    writeln!(f, "snake {} {}", config.snake_len, direction_name(config.direction))?;
+    if let Some(start) = config.start {
+        writeln!(f, "start {} {}", start.x, start.y)?;
+    }
+    writeln!(f, "growth {}", config.growth)?;
+    writeln!(f, "topology {}", match config.topology {
+        Topology::Wrapping => "wrapping",
+        Topology::Walled => "walled",
+    })?;
+    if let Some(seed) = config.seed {
+        writeln!(f, "seed {}", seed)?;
+    }
+    if let Some(ref map) = config.map {
+        let text = map.to_string();
+        writeln!(f, "map {}", text.lines().count())?;
+        write!(f, "{}", text)?;
+    }
+    writeln!(f, "end")
+}
+
+pub(crate) fn read_config(reader: &mut Reader) -> Result<GameConfig, ParseError> {
+    let mut config = GameConfig::new();
+    let [width, height] = reader.values("size")?;
+    config = config.size(reader.parse(width)?, reader.parse(height)?);
+    let [len, direction] = reader.values("snake")?;
+    config = config.snake_len(reader.parse(len)?).direction(reader.direction(direction)?);
+    loop {
+        let line = reader.raw_line()?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["start", x, y] => {
+                config = config.start(Point { x: reader.parse(x)?, y: reader.parse(y)? });
+            },
+            ["growth", growth] => config = config.growth(reader.parse(growth)?),
+            ["topology", "wrapping"] => config = config.topology(Topology::Wrapping),
+            ["topology", "walled"] => config = config.topology(Topology::Walled),
+            ["seed", seed] => config = config.seed(reader.parse(seed)?),
+            ["map", n] => {
+                let n = reader.parse(n)?;
+                let (first, text) = reader.block(n)?;
+                let map = Map::parse(&text).map_err(|e| map_error(first, e))?;
+                config = config.map(map);
+            },
+            ["end"] => break,
+            _ => return reader.error(format!("unexpected line {:?}", line)),
+        }
+    }
+    Ok(config)
+}
@@ -0,0 +1,42 @@
+use num_traits::NumCast;
+
+use crate::cell::*;
+use crate::point::*;
+
+
+/// What happens when the snake reaches an edge of the field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// edges are glued together, snake reappears on the opposite side.
+    #[default]
+    Wrapping,
+    /// edges are solid walls, crossing them is deadly.
+    Walled,
+}
+
+impl Topology {
+    /// move `point` one cell in given direction on a field of given `size`.
+    /// returns `None` if the point would leave a walled field.
+    pub fn advance<T: NumCast>(&self, point: Point<T>, direction: Direction, size: &dyn Size2D<usize>) -> Option<Point<T>> {
+        let next = direction.advance(point);
+        match *self {
+            Topology::Wrapping => Some(next.wrap(size)),
+            Topology::Walled if next.is_inside(size) => Some(next),
+            Topology::Walled => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn advance_across_edge() {
+        let size = (3usize, 2usize);
+        let p = Point::from((2isize, 0isize));
+        assert_eq!(Topology::Wrapping.advance(p, Right, &size), Some(Point::from((0, 0))));
+        assert_eq!(Topology::Walled.advance(p, Right, &size), None);
+        assert_eq!(Topology::Walled.advance(p, Down, &size), Some(Point::from((2, 1))));
+    }
+}
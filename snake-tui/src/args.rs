@@ -0,0 +1,280 @@
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use snake::*;
+
+use crate::{keys, paths};
+use crate::snake_window::RenderMode;
+use crate::theme::Theme;
+use crate::settings::{Settings, SPEEDS};
+
+const USAGE: &str = "\
+usage: snake-tui [options]
+
+options:
+    --map <file>    play on a level loaded from a text file
+    --seed <n>      seed for food placement, makes the game reproducible
+    --speed <n>     how fast the snake moves, from 1 to 12.  default is 6
+    --accelerate    speed up a level every 5 points
+    --theme <name>  glyphs and colors: unicode (default), ascii, emoji,
+                    high-contrast, colorblind, mono, one from the themes
+                    config directory, or a theme file
+    --render <mode> how field cells are drawn: normal, double for two columns
+                    per cell, or half-block for two cells per character.
+                    the last two make cells about square
+    --margin <n>    cells between the head and the edge of the view before a
+                    field larger than the terminal scrolls.  default is a
+                    quarter of the view, half of it keeps the head centered
+    --no-minimap    leave out the map of the whole field shown when it scrolls
+    --bot <name>    start a game played by a bot, skipping the main menu.
+                    greedy goes straight for the food, safe makes sure it can
+                    always get out again, and cycle goes through every cell in
+                    turn until the field is full.  the autopilot key, B, hands
+                    the game to the bot and back, safe unless one is given
+    --backend <name>
+                    terminal library to draw with: curses (default),
+                    crossterm, or plain, which only writes ANSI escapes and is
+                    used when output is not a terminal, printing every frame
+                    as text
+    --record <file> save a replay of the game when it ends
+    --resume        continue the game saved on quit, skipping the main menu
+    --replay <file> watch a recorded game.  keys: Space pauses, Right or .
+                    steps forward while paused, 1, 2 and 4 set the speed
+    -h, --help      print this help and exit
+
+keys are bound in $XDG_CONFIG_HOME/snake-rs/keys.toml, see README.";
+
+/// Terminal library the game is drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Curses,
+    Crossterm,
+    /// no library at all, see `PlainFrontend`.
+    Plain,
+}
+
+impl Backend {
+    /// whether support for this backend was compiled in.
+    pub fn is_available(self) -> bool {
+        match self {
+            Backend::Curses => cfg!(feature = "curses"),
+            Backend::Crossterm => cfg!(feature = "crossterm"),
+            Backend::Plain => true,
+        }
+    }
+
+    /// backend to use unless told otherwise.
+    /// output which is not a terminal gets plain text.
+    pub fn detect() -> Backend {
+        [Backend::Curses, Backend::Crossterm]
+            .iter()
+            .copied()
+            .find(|backend| backend.is_available() && io::stdout().is_terminal())
+            .unwrap_or(Backend::Plain)
+    }
+}
+
+/// Command line options.
+pub struct Args {
+    pub backend: Backend,
+    /// whether output goes to a terminal.  otherwise a single game is played, without menus.
+    pub interactive: bool,
+    /// from the config file, see `keys::load`.
+    pub keys: KeyBindings,
+    pub settings: Settings,
+    /// themes to choose from.
+    pub themes: Vec<Theme>,
+    /// where to save a replay of the game.
+    pub record: Option<PathBuf>,
+    /// recorded game to play back instead of a new one.
+    pub replay: Option<Replay>,
+    /// saved game to continue instead of starting a new one.
+    pub resume: Option<SavedGame>,
+}
+
+impl Args {
+    /// parse options of the current process.
+    /// `Err` holds a message to print before exiting.
+    pub fn parse() -> Result<Args, String> {
+        let mut config = GameConfig::new();
+        let mut speed = None;
+        let mut accelerate = false;
+        let mut theme = None;
+        let mut render = None;
+        let mut margin = None;
+        let mut minimap = true;
+        let mut bot = None;
+        let mut backend = Backend::detect();
+        let mut record = None;
+        let mut replay = None;
+        let mut resume = None;
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--map" => {
+                    let path = args.next().ok_or("--map requires a file")?;
+                    let text = fs::read_to_string(&path)
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                    let map = Map::parse(&text)
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                    config = config.map(map);
+                },
+                "--seed" => {
+                    let seed = args.next().ok_or("--seed requires a number")?;
+                    let seed = seed.parse().map_err(|_| format!("invalid seed: {}", seed))?;
+                    config = config.seed(seed);
+                },
+                "--speed" => {
+                    let value = args.next().ok_or("--speed requires a number")?;
+                    match value.parse() {
+                        Ok(n) if (SPEEDS.0..=SPEEDS.1).contains(&n) => speed = Some(n),
+                        _ => return Err(format!("invalid speed: {}", value)),
+                    }
+                },
+                "--accelerate" => accelerate = true,
+                "--render" => {
+                    let name = args.next().ok_or("--render requires a mode")?;
+                    render = Some(RenderMode::from_name(&name).ok_or_else(|| format!("unknown render mode: {}", name))?);
+                },
+                "--margin" => {
+                    let value = args.next().ok_or("--margin requires a number")?;
+                    margin = Some(value.parse().map_err(|_| format!("invalid margin: {}", value))?);
+                },
+                "--no-minimap" => minimap = false,
+                "--bot" => {
+                    let name = args.next().ok_or("--bot requires a name")?;
+                    bot = Some(BOTS.iter().copied().find(|&bot| bot == name).ok_or_else(|| format!("unknown bot: {}", name))?);
+                },
+                "--theme" => theme = Some(args.next().ok_or("--theme requires a name")?),
+                "--backend" => {
+                    let name = args.next().ok_or("--backend requires a name")?;
+                    backend = match name.as_str() {
+                        "curses" => Backend::Curses,
+                        "crossterm" => Backend::Crossterm,
+                        "plain" => Backend::Plain,
+                        _ => return Err(format!("unknown backend: {}", name)),
+                    };
+                    if !backend.is_available() {
+                        return Err(format!("snake-tui was built without {} support", name));
+                    }
+                },
+                "--record" => {
+                    record = Some(args.next().ok_or("--record requires a file")?.into());
+                },
+                "--replay" => {
+                    let path = args.next().ok_or("--replay requires a file")?;
+                    let text = fs::read_to_string(&path)
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                    let parsed: Replay = text.parse()
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                    parsed.config().validate()
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                    replay = Some(parsed);
+                },
+                "--resume" => resume = Some(load_saved_game()?),
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => return Err(format!("unknown option: {}\n\n{}", arg, USAGE)),
+            }
+        }
+        if record.is_some() && replay.is_some() {
+            return Err("--record and --replay can not be used together".to_string());
+        }
+        if resume.is_some() && replay.is_some() {
+            return Err("--resume and --replay can not be used together".to_string());
+        }
+        config.validate().map_err(|e| e.to_string())?;
+        let mut settings = Settings::new(config);
+        settings.speed = speed.unwrap_or(settings.speed);
+        settings.accelerate = accelerate;
+        settings.render = render.unwrap_or(settings.render);
+        settings.margin = margin;
+        settings.minimap = minimap;
+        settings.bot = bot.unwrap_or(settings.bot);
+        settings.autopilot = bot.is_some();
+        let mut themes = Theme::load_all()?;
+        if let Some(name) = theme {
+            settings.theme = match themes.iter().find(|theme| theme.name == name) {
+                Some(theme) => theme.clone(),
+                None if name.ends_with(".toml") => {
+                    // a file named like a theme replaces it, as in the themes directory.
+                    let theme = Theme::load(name.as_ref(), &themes)?;
+                    match themes.iter_mut().find(|t| t.name == theme.name) {
+                        Some(existing) => *existing = theme.clone(),
+                        None => themes.push(theme.clone()),
+                    }
+                    theme
+                },
+                None => return Err(format!("unknown theme: {}", name)),
+            };
+        }
+        let keys = keys::load()?;
+        let interactive = io::stdout().is_terminal();
+        Ok(Args { backend, interactive, keys, settings, themes, record, replay, resume })
+    }
+}
+
+/// Game saved on quit, with what is kept about it besides the game itself.
+pub struct SavedGame {
+    pub game: Game,
+    /// time it was played before it was saved, not counting pauses.
+    pub played: Duration,
+    /// whether a bot played any of it, which keeps it out of the high scores.
+    pub assisted: bool,
+}
+
+/// text of a save file: lines with the play time in milliseconds and whether a bot played,
+/// followed by the game as `Game::save` gives it.
+pub fn saved_game_text(game: &str, played: Duration, assisted: bool) -> String {
+    format!("played {}\nassisted {}\n{}", played.as_millis(), assisted, game)
+}
+
+fn parse_saved_game(text: &str) -> Result<SavedGame, String> {
+    let mut lines = text.splitn(3, '\n');
+    let played = match lines.next().and_then(|line| line.strip_prefix("played ")).map(str::parse) {
+        Some(Ok(ms)) => Duration::from_millis(ms),
+        _ => return Err("line 1: expected \"played\"".to_string()),
+    };
+    let assisted = match lines.next() {
+        Some("assisted true") => true,
+        Some("assisted false") => false,
+        _ => return Err("line 2: expected \"assisted\"".to_string()),
+    };
+    // line numbers count from the top of the file.
+    let game = Game::restore(lines.next().unwrap_or_default())
+        .map_err(|e| ParseError { line: e.line + 2, ..e }.to_string())?;
+    Ok(SavedGame { game, played, assisted })
+}
+
+/// game saved on quit, paused so the player can get ready.
+/// the save is removed once loaded, so the game can only be continued once.
+pub fn load_saved_game() -> Result<SavedGame, String> {
+    let path = paths::save_file().ok_or("can not find data directory")?;
+    let error = |e: String| format!("{}: {}", path.display(), e);
+    let text = fs::read_to_string(&path).map_err(|e| error(e.to_string()))?;
+    let mut saved = parse_saved_game(&text).map_err(error)?;
+    fs::remove_file(&path).map_err(|e| error(e.to_string()))?;
+    saved.game.pause();
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn saved_game_round_trip() {
+        let game = Game::with_seed(3);
+        let text = saved_game_text(&game.save(), Duration::from_millis(61_500), true);
+        let saved = parse_saved_game(&text).unwrap();
+        assert_eq!((saved.played, saved.assisted), (Duration::from_millis(61_500), true));
+        assert_eq!(saved.game.save(), game.save());
+
+        // errors in the game are reported at their line in the file.
+        let text = saved_game_text("snake-save 7\n", Duration::ZERO, false);
+        assert_eq!(parse_saved_game(&text).err(), Some("line 3: unsupported snake-save version 7".to_string()));
+        assert_eq!(parse_saved_game(&game.save()).err(), Some("line 1: expected \"played\"".to_string()));
+    }
+}
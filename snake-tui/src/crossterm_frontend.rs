@@ -0,0 +1,142 @@
+use std::io::{self, Stdout, Write};
+use std::time::Duration;
+
+use crossterm::{cursor, event, queue, style, terminal};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+
+use snake::*;
+
+/// Frontend drawing on the terminal with crossterm, which needs no C library.
+pub struct CrosstermFrontend {
+    stdout: Stdout,
+    /// frame on the screen, to only redraw what changed.
+    shown: Option<Frame>,
+}
+
+impl CrosstermFrontend {
+    /// take over the terminal until dropped.
+    pub fn new() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        queue!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        stdout.flush()?;
+        Ok(CrosstermFrontend { stdout, shown: None })
+    }
+
+    fn draw(&mut self, frame: &Frame) -> io::Result<()> {
+        let previous = self.shown.take().filter(|shown| shown.size() == frame.size());
+        if previous.is_none() {
+            queue!(self.stdout, terminal::Clear(terminal::ClearType::All))?;
+        }
+        let mut current_style = None;
+        for y in 0..frame.height() {
+            // the cursor moves by itself along runs of changed cells.
+            let mut cursor_at = None;
+            for x in 0..frame.width() {
+                let cell = frame.get(x, y).unwrap();
+                if previous.as_ref().and_then(|shown| shown.get(x, y)) == Some(cell)
+                    || cell.ch == StyledCell::CONTINUATION {
+                    continue;
+                }
+                if cursor_at != Some(x) {
+                    queue!(self.stdout, cursor::MoveTo(x as u16, y as u16))?;
+                }
+                if current_style != Some(cell.style) {
+                    set_style(&mut self.stdout, cell.style)?;
+                    current_style = Some(cell.style);
+                }
+                queue!(self.stdout, style::Print(cell.ch))?;
+                cursor_at = Some(x + 1);
+            }
+        }
+        if current_style.is_some() {
+            queue!(self.stdout, style::SetAttribute(style::Attribute::Reset))?;
+        }
+        self.stdout.flush()?;
+        self.shown = Some(frame.clone());
+        Ok(())
+    }
+}
+
+impl Drop for CrosstermFrontend {
+    fn drop(&mut self) {
+        let _ = queue!(self.stdout, style::ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = self.stdout.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Frontend for CrosstermFrontend {
+    fn size(&self) -> (usize, usize) {
+        match terminal::size() {
+            Ok((width, height)) if width > 0 && height > 0 => (width as usize, height as usize),
+            // size is unknown, e.g. on a pseudo-terminal nobody has set it for.
+            _ => (80, 24),
+        }
+    }
+
+    fn show(&mut self, frame: &Frame) {
+        if self.draw(frame).is_err() {
+            // start over with a full redraw next time.
+            self.shown = None;
+        }
+    }
+
+    fn read_key(&mut self, timeout: Option<Duration>) -> Option<Key> {
+        if let Some(timeout) = timeout {
+            if !event::poll(timeout).ok()? {
+                return None;
+            }
+        }
+        match event::read().ok()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => Some(match key.code {
+                KeyCode::Up => Key::Up,
+                KeyCode::Down => Key::Down,
+                KeyCode::Left => Key::Left,
+                KeyCode::Right => Key::Right,
+                KeyCode::Enter => Key::Enter,
+                KeyCode::Esc => Key::Escape,
+                KeyCode::Backspace => Key::Backspace,
+                // raw mode turns Ctrl-C into a key press, which gets out of wherever the player is.
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Key::Escape,
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => Key::Char(c),
+                _ => return None,
+            }),
+            Event::Resize(_, _) => {
+                self.shown = None;
+                Some(Key::Resize)
+            },
+            _ => None,
+        }
+    }
+}
+
+fn set_style(stdout: &mut Stdout, cell_style: Style) -> io::Result<()> {
+    use crossterm::style::Attribute;
+
+    queue!(stdout,
+           style::SetAttribute(Attribute::Reset),
+           style::SetForegroundColor(crossterm_color(cell_style.fg)),
+           style::SetBackgroundColor(crossterm_color(cell_style.bg)))?;
+    if cell_style.bold {
+        queue!(stdout, style::SetAttribute(Attribute::Bold))?;
+    }
+    if cell_style.reverse {
+        queue!(stdout, style::SetAttribute(Attribute::Reverse))?;
+    }
+    Ok(())
+}
+
+fn crossterm_color(color: Color) -> style::Color {
+    match color {
+        Color::Default => style::Color::Reset,
+        Color::Black => style::Color::Black,
+        Color::Red => style::Color::DarkRed,
+        Color::Green => style::Color::DarkGreen,
+        Color::Yellow => style::Color::DarkYellow,
+        Color::Blue => style::Color::DarkBlue,
+        Color::Magenta => style::Color::DarkMagenta,
+        Color::Cyan => style::Color::DarkCyan,
+        Color::White => style::Color::Grey,
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use pancurses::{self, chtype, Input, Window};
+
+use snake::*;
+
+/// Frontend drawing on the terminal with pancurses.
+pub struct CursesFrontend {
+    window: Window,
+    /// color pairs set up so far, by foreground and background.
+    pairs: HashMap<(Color, Color), i16>,
+}
+
+impl CursesFrontend {
+    /// take over the terminal until dropped.
+    pub fn new() -> Self {
+        let window = pancurses::initscr();
+        window.keypad(true);
+        // drop keys pressed before the game started.
+        window.nodelay(true);
+        while window.getch().is_some() {}
+        pancurses::noecho();
+        pancurses::curs_set(0);
+        if pancurses::has_colors() {
+            pancurses::start_color();
+            pancurses::use_default_colors();
+        }
+        CursesFrontend { window, pairs: HashMap::new() }
+    }
+
+    /// color pair for given colors, set up on first use.
+    /// falls back to default colors when the terminal has no more pairs.
+    fn pair(&mut self, fg: Color, bg: Color) -> chtype {
+        if (fg, bg) == (Color::Default, Color::Default) || !pancurses::has_colors() {
+            return 0;
+        }
+        let next = self.pairs.len() as i16 + 1;
+        let pair = *self.pairs.entry((fg, bg)).or_insert_with(|| {
+            if (next as i32) < pancurses::COLOR_PAIRS() {
+                pancurses::init_pair(next, curses_color(fg), curses_color(bg));
+                next
+            } else {
+                0
+            }
+        });
+        pair as chtype
+    }
+
+    fn attributes(&mut self, style: Style) -> chtype {
+        let mut attributes = pancurses::COLOR_PAIR(self.pair(style.fg, style.bg));
+        if style.bold {
+            attributes |= pancurses::A_BOLD;
+        }
+        if style.reverse {
+            attributes |= pancurses::A_REVERSE;
+        }
+        attributes
+    }
+}
+
+impl Drop for CursesFrontend {
+    fn drop(&mut self) {
+        self.window.keypad(false);
+        self.window.nodelay(false);
+        pancurses::endwin();
+    }
+}
+
+impl Frontend for CursesFrontend {
+    fn size(&self) -> (usize, usize) {
+        let (height, width) = self.window.get_max_yx();
+        (width.max(0) as usize, height.max(0) as usize)
+    }
+
+    fn show(&mut self, frame: &Frame) {
+        let mut buffer = [0; 4];
+        for y in 0..frame.height() {
+            for x in 0..frame.width() {
+                let cell = frame.get(x, y).unwrap();
+                if cell.ch == StyledCell::CONTINUATION {
+                    continue;
+                }
+                let attributes = self.attributes(cell.style);
+                self.window.attrset(attributes);
+                self.window.mvaddstr(y as i32, x as i32, cell.ch.encode_utf8(&mut buffer));
+            }
+        }
+        self.window.attrset(pancurses::A_NORMAL);
+        self.window.refresh();
+    }
+
+    fn read_key(&mut self, timeout: Option<Duration>) -> Option<Key> {
+        // round up, so that the timeout is never cut short.
+        let millis = timeout.map_or(-1, |t| (t + Duration::from_micros(999)).as_millis() as i32);
+        self.window.timeout(millis);
+        Some(match self.window.getch()? {
+            Input::KeyUp => Key::Up,
+            Input::KeyDown => Key::Down,
+            Input::KeyLeft => Key::Left,
+            Input::KeyRight => Key::Right,
+            Input::KeyEnter | Input::Character('\n') | Input::Character('\r') => Key::Enter,
+            Input::Character('\u{1b}') => Key::Escape,
+            Input::KeyBackspace | Input::Character('\u{7f}') | Input::Character('\u{8}') => Key::Backspace,
+            Input::KeyResize => {
+                pancurses::resize_term(0, 0);
+                Key::Resize
+            },
+            Input::Character(c) => Key::Char(c),
+            _ => return None,
+        })
+    }
+}
+
+fn curses_color(color: Color) -> i16 {
+    match color {
+        Color::Default => -1,
+        Color::Black => pancurses::COLOR_BLACK,
+        Color::Red => pancurses::COLOR_RED,
+        Color::Green => pancurses::COLOR_GREEN,
+        Color::Yellow => pancurses::COLOR_YELLOW,
+        Color::Blue => pancurses::COLOR_BLUE,
+        Color::Magenta => pancurses::COLOR_MAGENTA,
+        Color::Cyan => pancurses::COLOR_CYAN,
+        Color::White => pancurses::COLOR_WHITE,
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use snake::*;
+
+/// how long to wait for another game to finish writing its score.
+const LOCK_WAIT: Duration = Duration::from_secs(5);
+/// lock older than this was left behind by a game that crashed.
+const STALE_LOCK: Duration = Duration::from_secs(30);
+
+/// high score table kept in given file, empty if there is none yet.
+pub fn load(path: &Path) -> Result<ScoreTable, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => text.parse().map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(ScoreTable::new()),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// add a game to the table kept in given file.
+/// returns the updated table and the place the game took in it, if any.
+///
+/// games which end at once take turns through a lock file, so none of their scores is lost,
+/// and the file is replaced as a whole by renaming, so it is never left half written.
+pub fn record(path: &Path, entry: HighScore) -> Result<(ScoreTable, Option<usize>), String> {
+    let error = |e: io::Error| format!("{}: {}", path.display(), e);
+    fs::create_dir_all(path.parent().unwrap()).map_err(error)?;
+    let _lock = Lock::acquire(path.with_extension("lock"))?;
+    let mut table = load(path)?;
+    let rank = table.insert(entry);
+    if rank.is_some() {
+        let temp = path.with_extension(format!("{}.tmp", process::id()));
+        let write = || {
+            let mut file = File::create(&temp)?;
+            file.write_all(table.to_string().as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp, path)
+        };
+        if let Err(e) = write() {
+            let _ = fs::remove_file(&temp);
+            return Err(error(e));
+        }
+    }
+    Ok((table, rank))
+}
+
+/// Lock file which exists for as long as its owner holds the lock.
+struct Lock(PathBuf);
+
+impl Lock {
+    fn acquire(path: PathBuf) -> Result<Lock, String> {
+        let start = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Lock(path)),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok());
+                    if age.is_some_and(|age| age > STALE_LOCK) {
+                        let _ = fs::remove_file(&path);
+                    } else if start.elapsed() > LOCK_WAIT {
+                        return Err("high scores are locked by another game".to_string());
+                    } else {
+                        thread::sleep(Duration::from_millis(20));
+                    }
+                },
+                Err(e) => return Err(format!("{}: {}", path.display(), e)),
+            }
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// empty directory of its own for a test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("snake-tui-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn entry(name: &str, score: usize) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            length: score + 5,
+            duration: Duration::from_secs(42),
+            date: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn record_scores() {
+        let dir = temp_dir("record");
+        // the directory is created as needed.
+        let path = dir.join("scores").join("20x10");
+        let (_, rank) = record(&path, entry("first", 3)).unwrap();
+        assert_eq!(rank, Some(0));
+        let (table, rank) = record(&path, entry("better", 7)).unwrap();
+        assert_eq!(rank, Some(0));
+        assert_eq!(record(&path, entry("worse", 5)).unwrap().1, Some(1));
+        assert_eq!(record(&path, entry("nothing", 0)).unwrap().1, None);
+
+        let names: Vec<&str> = table.entries().iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["better", "first"]);
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.entries().len(), 3);
+        assert_eq!(loaded.entries()[1], entry("worse", 5));
+        assert!(!path.with_extension("lock").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn lock_waits_for_owner() {
+        let dir = temp_dir("lock");
+        let path = dir.join("scores.lock");
+        let lock = Lock::acquire(path.clone()).unwrap();
+        let start = Instant::now();
+        let owner = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            drop(lock);
+        });
+        let second = Lock::acquire(path.clone()).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        owner.join().unwrap();
+        assert!(path.exists());
+        drop(second);
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stale_temp_file() {
+        let dir = temp_dir("temp");
+        let path = dir.join("20x10");
+        record(&path, entry("first", 3)).unwrap();
+
+        // left behind by a game that crashed while writing, maybe one with the same pid.
+        let garbage = "half written\n".repeat(100);
+        fs::write(path.with_extension("999999.tmp"), &garbage).unwrap();
+        fs::write(path.with_extension(format!("{}.tmp", process::id())), &garbage).unwrap();
+        let (table, rank) = record(&path, entry("second", 2)).unwrap();
+        assert_eq!(rank, Some(1));
+        assert_eq!(load(&path).unwrap(), table);
+        assert_eq!(table.entries(), [entry("first", 3), entry("second", 2)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use toml::{Table, Value};
+
+use snake::*;
+
+use crate::paths;
+
+/// key bindings from the config file, or the default ones if there is none.
+///
+/// the file picks a preset, then binds actions to one key or a list of them,
+/// replacing the keys the preset had for them:
+///
+/// ```toml
+/// preset = "vim"
+///
+/// [keys]
+/// quit = ["q", "escape"]
+/// speed-up = []
+/// ```
+pub fn load() -> Result<KeyBindings, String> {
+    let path = match paths::keys_file() {
+        Some(path) => path,
+        None => return Ok(KeyBindings::default()),
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(KeyBindings::default()),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn parse(text: &str) -> Result<KeyBindings, String> {
+    let mut table: Table = text.parse().map_err(|e: toml::de::Error| {
+        let line = e.span().map_or(0, |span| text[..span.start].matches('\n').count() + 1);
+        format!("line {}: {}", line, e.message().trim().replace('\n', ", "))
+    })?;
+    let mut bindings = match table.remove("preset") {
+        None => KeyBindings::default(),
+        Some(Value::String(name)) => KeyBindings::preset(&name).ok_or_else(|| {
+            format!("unknown preset {:?}, expected one of: {}", name, KeyBindings::PRESETS.join(", "))
+        })?,
+        Some(_) => return Err("preset must be a string".to_string()),
+    };
+    let keys = match table.remove("keys") {
+        None => Table::new(),
+        Some(Value::Table(keys)) => keys,
+        Some(_) => return Err("keys must be a table".to_string()),
+    };
+    if let Some(name) = table.keys().next() {
+        return Err(format!("unknown setting {:?}", name));
+    }
+    // keys bound by the file, which can take a key from the preset but not give it two actions.
+    let mut bound: Vec<(Key, Action)> = Vec::new();
+    for (name, value) in keys {
+        let action = Action::from_name(&name).ok_or_else(|| format!("unknown action {:?}", name))?;
+        let names = match value {
+            Value::String(name) => vec![Value::String(name)],
+            Value::Array(names) => names,
+            _ => return Err(format!("{}: expected a key or a list of keys", name)),
+        };
+        bindings.clear(action);
+        for key in names {
+            let key = key.as_str().and_then(Key::from_name)
+                .ok_or_else(|| format!("{}: invalid key {}", name, key))?;
+            if let Some(&(_, other)) = bound.iter().find(|&&(k, a)| k == key && a != action) {
+                return Err(format!("key {:?} is bound to both {} and {}", key.name(), other.name(), action.name()));
+            }
+            bound.push((key, action));
+            bindings.bind(key, action);
+        }
+    }
+    Ok(bindings)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vim_preset() {
+        let bindings = parse("preset = \"vim\"").unwrap();
+        assert_eq!(bindings, KeyBindings::vim());
+        assert_eq!(bindings.action(Key::Char('h')), Some(Action::Turn(Left)));
+        assert_eq!(bindings.action(Key::Char('w')), None);
+        assert_eq!(bindings.action(Key::Up), Some(Action::Turn(Up)));
+        assert_eq!(parse("").unwrap(), KeyBindings::default());
+    }
+
+    #[test]
+    fn rebind_one_action() {
+        let bindings = parse("[keys]\nquit = [\"escape\", \"e\"]").unwrap();
+        assert_eq!(bindings.keys(Action::Quit).collect::<Vec<_>>(), [Key::Escape, Key::Char('e')]);
+        assert_eq!(bindings.action(Key::Char('q')), None);
+
+        // everything else keeps the keys of the preset.
+        let mut expected = KeyBindings::default();
+        expected.clear(Action::Quit);
+        expected.bind(Key::Escape, Action::Quit);
+        expected.bind(Key::Char('e'), Action::Quit);
+        assert_eq!(bindings, expected);
+    }
+
+    #[test]
+    fn invalid_names() {
+        assert_eq!(parse("[keys]\njump = \"j\"").unwrap_err(), "unknown action \"jump\"");
+        assert_eq!(parse("[keys]\nquit = \"ctrl-q\"").unwrap_err(), "quit: invalid key \"ctrl-q\"");
+        assert_eq!(parse("[keys]\nquit = 1").unwrap_err(), "quit: expected a key or a list of keys");
+        assert!(parse("preset = \"emacs\"").unwrap_err().starts_with("unknown preset \"emacs\""));
+        assert_eq!(parse("speed = 3").unwrap_err(), "unknown setting \"speed\"");
+    }
+
+    #[test]
+    fn key_bound_twice() {
+        // a key does one thing only, so binding it again takes it from the action it had.
+        let bindings = parse("[keys]\npause = \"q\"").unwrap();
+        assert_eq!(bindings.action(Key::Char('q')), Some(Action::Pause));
+        assert_eq!(bindings.keys(Action::Quit).count(), 0);
+        assert_eq!(bindings.action(Key::Char(' ')), None);
+
+        // but a file can not give a key two actions.
+        assert_eq!(parse("[keys]\nquit = \"p\"\npause = [\"space\", \"p\"]").unwrap_err(),
+                   "key \"p\" is bound to both pause and quit");
+        assert!(parse("[keys]\nquit = [\"q\", \"q\"]").is_ok());
+    }
+}
@@ -1,186 +1,525 @@
-use std::{
-    ops::Deref,
-    sync::{Arc, Mutex, mpsc::channel},
-    thread,
-    time,
-};
-
-use pancurses::{initscr, endwin};
-use pancurses::Window;
-use pancurses::Input::*;
+use std::env;
+use std::fs;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use snake::*;
 
-use crate::curses_window::*;
+use crate::args::{Args, Backend};
+use crate::menu::{Menu, MenuInput};
+use crate::options::Options;
+use crate::playback::Playback;
+use crate::settings::{Settings, SPEEDS};
+use crate::snake_window::{draw_too_small, GameView, NameEntry, PauseView, ScoresView, NAME_LEN};
 
-mod curses_window;
+mod args;
+mod high_scores;
+mod keys;
+#[cfg(feature = "crossterm")]
+mod crossterm_frontend;
+#[cfg(feature = "curses")]
+mod curses_frontend;
+mod menu;
+mod options;
+mod paths;
+mod plain_frontend;
+mod playback;
+mod settings;
 mod snake_window;
+mod theme;
 
-/// Required wrapper for using `pancurses::Window` in multithreaded environment.
-struct MyWin(Window);
-
-unsafe impl std::marker::Send for MyWin {}
-
-impl Deref for MyWin {
-    type Target = Window;
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
-
-impl Into<MyWin> for pancurses::Window {
-    #[inline]
-    fn into(self) -> MyWin { MyWin(self) }
+/// How playing a game came to an end.
+enum Ending {
+    Over,
+    /// player quit, leaving the game as it was before.
+    Quit(String),
+    Restart,
 }
 
 struct Application {
-    root_window: pancurses::Window,
-    window: Option<Arc<Mutex<MyWin>>>,
-    game: Option<Arc<Mutex<Game>>>,
+    args: Args,
+    frontend: Box<dyn Frontend>,
+    game: Option<Game>,
+    playback: Option<Playback>,
+    /// time the current game has been played, not counting pauses.
+    played: Duration,
+    /// name last put next to a high score.
+    player: String,
+    /// part of a field larger than the screen in view.
+    camera: Camera,
+    /// bot playing the current game instead of the player.
+    bot: Option<Box<dyn Strategy>>,
+    /// whether a bot has played any of the current game, which keeps it out of the high scores.
+    assisted: bool,
 }
 
 impl Application {
-    pub fn new() -> Application {
+    pub fn new(args: Args, frontend: Box<dyn Frontend>) -> Application {
         Application {
-            root_window: initscr(),
-            window: None,
+            args,
+            frontend,
             game: None,
+            playback: None,
+            played: Duration::ZERO,
+            player: env::var("USER").unwrap_or_default().chars().take(NAME_LEN).collect(),
+            camera: Camera::default(),
+            bot: None,
+            assisted: false,
         }
     }
 
-    pub fn start(&mut self) {
-        self.set_up();
-
-        let (tx, rx) = channel();
-        {
-            let window_arc: Arc<Mutex<MyWin>> = self.window.as_ref().unwrap().clone();
-            let window: &Window = &**window_arc.lock().unwrap();
-            let game: Arc<Mutex<Game>> = self.game.as_ref().unwrap().clone();
+    /// run the game.  `Err` holds a message to print after the terminal is restored.
+    pub fn start(&mut self) -> Result<(), String> {
+        // a game given on the command line is played before the main menu is shown,
+        // and so is a new one for a bot to play, or when output is not a terminal,
+        // which gets no menus at all.
+        if let Some(replay) = self.args.replay.take() {
+            self.start_replay(&replay);
+            self.run_games()?;
+        } else if let Some(saved) = self.args.resume.take() {
+            self.game = Some(saved.game);
+            self.played = saved.played;
+            self.assisted = saved.assisted;
+            if self.run_games()? {
+                return Ok(());
+            }
+        } else if !self.args.interactive || self.args.settings.autopilot {
+            self.new_game();
+            if self.run_games()? {
+                return Ok(());
+            }
+        }
+        if !self.args.interactive {
+            return Ok(());
+        }
+        self.main_menu()
+    }
 
-            {
-                let (game, tx) = (game.clone(), tx.clone());
-                let g_size = game.lock().unwrap().win_size();
-                let sub_win = MyWin(window.derwin(g_size.0, g_size.1, 0, 0).unwrap());
+    fn main_menu(&mut self) -> Result<(), String> {
+        let mut menu = Menu::new("Snake", vec![
+            "New game".to_string(),
+            "Load".to_string(),
+            "High scores".to_string(),
+            "Options".to_string(),
+            "Quit".to_string(),
+        ]);
+        loop {
+            let background = self.blank_screen();
+            let choice = menu.run(&mut *self.frontend, &background);
+            menu.set_title("Snake");
+            let quit = match choice {
+                MenuInput::Chosen(0) => {
+                    self.new_game();
+                    self.run_games()?
+                },
+                MenuInput::Chosen(1) => match args::load_saved_game() {
+                    Ok(saved) => {
+                        self.playback = None;
+                        self.game = Some(saved.game);
+                        self.played = saved.played;
+                        self.assisted = saved.assisted;
+                        self.run_games()?
+                    },
+                    Err(message) => {
+                        menu.set_title(message);
+                        false
+                    },
+                },
+                MenuInput::Chosen(2) => {
+                    let config = self.args.settings.config.clone();
+                    if let Err(message) = self.show_scores(&config, None) {
+                        menu.set_title(message);
+                    }
+                    false
+                },
+                MenuInput::Chosen(3) => {
+                    self.edit_settings();
+                    false
+                },
+                MenuInput::Chosen(4) | MenuInput::Cancelled => true,
+                // Left and Right have nothing to change here, so the menu is just shown again.
+                MenuInput::Chosen(_) | MenuInput::Adjusted(..) | MenuInput::Resized => false,
+            };
+            if quit {
+                return Ok(());
+            }
+        }
+    }
 
-                thread::spawn(move || {
-                    Application::render_loop(game, sub_win);
-                    tx.send(()).unwrap();
-                });
+    /// play the current game, then more on request.
+    /// returns `true` if the player chose to quit rather than go back to the main menu.
+    fn run_games(&mut self) -> Result<bool, String> {
+        loop {
+            let ending = self.play();
+            self.write_record()?;
+            match ending {
+                Ending::Quit(snapshot) => {
+                    self.offer_save(&snapshot)?;
+                    return Ok(false);
+                },
+                Ending::Restart => {
+                    self.new_game();
+                    continue;
+                },
+                Ending::Over => (),
+            }
+            // replays have nothing to restart.
+            if self.playback.is_some() {
+                return Ok(false);
             }
-            {
-                let (game, tx) = (game.clone(), tx.clone());
-                let window = MyWin(window.derwin(1, 1, 0, 0).unwrap());
-                thread::spawn(move || {
-                    Application::interaction_loop(game, window);
-                    tx.send(()).unwrap();
-                });
+            // nobody is there to choose what comes next.
+            if !self.args.interactive {
+                return Ok(true);
             }
+            let title = match self.record_high_score() {
+                Ok(()) => "Game over".to_string(),
+                Err(message) => message,
+            };
+            let mut menu = Menu::new(title, vec![
+                "Restart".to_string(),
+                "Change settings".to_string(),
+                "Main menu".to_string(),
+                "Quit".to_string(),
+            ]);
+            let choice = loop {
+                // the menu is shown over the final state of the game.
+                let (background, _) = self.game_screen();
+                match menu.run(&mut *self.frontend, &background) {
+                    // Left and Right have nothing to change here.
+                    MenuInput::Resized | MenuInput::Adjusted(..) => continue,
+                    choice => break choice,
+                }
+            };
+            match choice {
+                MenuInput::Chosen(0) => {},
+                MenuInput::Chosen(1) => self.edit_settings(),
+                MenuInput::Chosen(3) => return Ok(true),
+                MenuInput::Chosen(2) | MenuInput::Cancelled => return Ok(false),
+                _ => unreachable!("the menu has four items and is shown again on other input"),
+            }
+            self.new_game();
         }
-        // 2 threads: one for keyboard interaction, one for render.
-        rx.recv().unwrap();
-        rx.recv().unwrap();
     }
 
-    fn render_loop(game: Arc<Mutex<Game>>, window: MyWin) {
-        while game.lock().unwrap().state() != GameState::GameOver {
-            window.clear();
+    fn edit_settings(&mut self) {
+        let settings = Options::new(self.args.settings.clone(), self.args.themes.clone()).run(&mut *self.frontend);
+        if let Some(settings) = settings {
+            self.args.settings = settings;
+        }
+    }
 
-            if let Ok(mut game_lock) = game.try_lock() {
-                if !game_lock.is_paused() {
-                    game_lock.step();
-                }
-                game_lock.draw(&window);
+    /// ask for a name if the game just over made it into the high scores,
+    /// then record it and show where it stands.
+    fn record_high_score(&mut self) -> Result<(), String> {
+        let game = self.game.as_ref().unwrap();
+        if self.assisted {
+            return Ok(());
+        }
+        let path = match paths::scores_file(game.config()) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let rank = match high_scores::load(&path)?.rank(game.score()) {
+            Some(rank) => rank,
+            None => return Ok(()),
+        };
+        let mut entry = NameEntry { rank, name: self.player.clone() };
+        loop {
+            let (background, _) = self.game_screen();
+            match entry.run(&mut *self.frontend, &background) {
+                MenuInput::Resized => continue,
+                MenuInput::Cancelled => return Ok(()),
+                _ => break,
             }
+        }
+        self.player = entry.name.trim().to_string();
+        let game = self.game.as_ref().unwrap();
+        let score = HighScore {
+            name: self.player.clone(),
+            score: game.score(),
+            length: game.snake_len(),
+            duration: self.played,
+            date: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()),
+        };
+        let config = game.config().clone();
+        let (_, rank) = high_scores::record(&path, score)?;
+        self.show_scores(&config, rank)
+    }
 
-            window.refresh();
-            thread::sleep(time::Duration::from_millis(1000));
+    /// high scores of games played with given config, until any key.
+    fn show_scores(&mut self, config: &GameConfig, highlight: Option<usize>) -> Result<(), String> {
+        let path = paths::scores_file(config).ok_or("can not find data directory")?;
+        let table = high_scores::load(&path)?;
+        let board = board_name(config);
+        let view = ScoresView { board: &board, table: &table, highlight };
+        loop {
+            let mut screen = self.blank_screen();
+            let (x, y) = screen.center_of(view.size());
+            screen.blit(&view.render(), x.max(0), y.max(0));
+            self.frontend.show(&screen);
+            match self.frontend.read_key(None) {
+                Some(Key::Resize) | None => (),
+                Some(_) => return Ok(()),
+            }
         }
-        // final draw call
-        game.lock().unwrap().draw(&window);
-        window.refresh();
     }
 
-    fn interaction_loop(game: Arc<Mutex<Game>>, window: MyWin) {
-        window.nodelay(false);
-        window.timeout(100);
-        window.keypad(true);
+    /// play the current game until it is over, or the player quits or restarts it.
+    ///
+    /// waits for keys until the next tick is due, then makes every tick that is due,
+    /// so the game runs at a steady rate however often keys are pressed.
+    /// the game is held while the screen is too small to show it.
+    fn play(&mut self) -> Ending {
+        let delay = |settings: &Settings, game: &Game, playback: &Option<Playback>| {
+            let tick = settings.tick(game.score());
+            match playback {
+                Some(ref playback) => playback.delay(tick),
+                None => tick,
+            }
+        };
+        let mut deadline = Instant::now() + delay(&self.args.settings, self.game.as_ref().unwrap(), &self.playback);
+        let mut last = Instant::now();
+        self.camera = Camera::new(self.args.settings.margin);
+        self.bot = match self.playback {
+            Some(_) => None,
+            None if self.args.settings.autopilot => bot(self.args.settings.bot),
+            None => None,
+        };
+        let ending = loop {
+            self.camera = self.game_view().follow();
+            let (screen, fits) = self.game_screen();
+            self.frontend.show(&screen);
+            let game = self.game.as_mut().unwrap();
+            if game.is_over() {
+                break Ending::Over;
+            }
 
-        while game.lock().unwrap().state() != GameState::GameOver {
-            match window.getch() {
-                Some(input) => match input {
-                    Character('q') => {
-                        game.lock().unwrap().quit();
-                    }
-                    Character(' ') => {
-                        game.lock().unwrap().pause();
-                    }
-                    key => {
-                        if let Some(dir) = Direction::from_input(key) {
-                            game.lock().unwrap().turn(dir).ok();
-                            game.lock().unwrap().unpause();
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.frontend.read_key(Some(timeout)) {
+                // the next frame is laid out for the new size.
+                Some(Key::Resize) | None => (),
+                Some(key) => match self.playback {
+                    Some(ref mut playback) => Application::playback_input(game, playback, &self.args.keys, key),
+                    None => {
+                        let action = self.args.keys.action(key);
+                        match action {
+                            Some(Action::Autopilot) => {
+                                self.bot = match self.bot {
+                                    Some(_) => None,
+                                    None => bot(self.args.settings.bot),
+                                };
+                                game.unpause();
+                            },
+                            // turning takes the game back from the bot.
+                            Some(Action::Turn(_)) | Some(Action::TurnLeft) | Some(Action::TurnRight) => self.bot = None,
+                            _ => (),
+                        }
+                        if let Some(ending) = Application::game_input(game, &mut self.args.settings, action) {
+                            break ending;
                         }
                     },
                 },
-                _ => (),
+            }
+
+            let now = Instant::now();
+            let held = !fits || (game.is_paused() && self.playback.is_none());
+            if !held {
+                self.played += now - last;
+            }
+            last = now;
+            if held {
+                // the first tick after a pause is a whole tick away.
+                deadline = now + delay(&self.args.settings, game, &self.playback);
+            }
+            while deadline <= now && !game.is_over() {
+                match self.playback {
+                    Some(ref mut playback) => playback.tick(game),
+                    None => {
+                        if let Some(ref mut bot) = self.bot {
+                            if game.queued_turns().next().is_none() {
+                                game.turn(bot.next_move(game)).ok();
+                            }
+                            self.assisted = true;
+                        }
+                        game.step();
+                    },
+                }
+                deadline += delay(&self.args.settings, game, &self.playback);
+            }
+        };
+        if self.playback.is_some() {
+            // keep the end of the replay on screen until any key.
+            while let Some(Key::Resize) | None = self.frontend.read_key(None) {
+                let (screen, _) = self.game_screen();
+                self.frontend.show(&screen);
             }
         }
-        // reset timeout and wait for any key.
-        window.timeout(-1);
-        let _ = window.getch();
+        ending
     }
 
-    fn skip_buffered_input(&self) {
-        while let Some(_input) = self.root_window.getch() {}
+    /// controls for replay playback: pause, step forward, and speed.
+    fn playback_input(game: &mut Game, playback: &mut Playback, keys: &KeyBindings, key: Key) {
+        match key {
+            Key::Char('.') | Key::Right => playback.step_forward(),
+            Key::Char(c @ '1') | Key::Char(c @ '2') | Key::Char(c @ '4') => {
+                playback.set_speed(c.to_digit(10).unwrap());
+            },
+            key => match keys.action(key) {
+                Some(Action::Quit) => game.quit(),
+                Some(Action::Pause) => if game.is_paused() { game.unpause() } else { game.pause() },
+                _ => (),
+            },
+        }
     }
 
-    fn set_up(&mut self) {
-        let window = &self.root_window;
-        window.keypad(true);
-        window.nodelay(true);
-        self.skip_buffered_input();
-        pancurses::noecho();
-        pancurses::curs_set(0);
-        { // colors
-            use pancurses::*;
+    /// what actions bound to keys do to the game.
+    /// returns how the game ended, if the player quit or restarted it.
+    fn game_input(game: &mut Game, settings: &mut Settings, action: Option<Action>) -> Option<Ending> {
+        let turn = match action? {
+            Action::Quit => {
+                game.pause();
+                let snapshot = game.save();
+                game.quit();
+                return Some(Ending::Quit(snapshot));
+            },
+            Action::Restart => return Some(Ending::Restart),
+            Action::Pause => {
+                if game.is_paused() { game.unpause() } else { game.pause() }
+                return None;
+            },
+            Action::SpeedUp => {
+                settings.speed = (settings.speed + 1).min(SPEEDS.1);
+                return None;
+            },
+            Action::Autopilot => return None,
+            Action::Turn(dir) => dir,
+            Action::TurnLeft => game.heading().turn_left(),
+            Action::TurnRight => game.heading().turn_right(),
+        };
+        game.turn(turn).ok();
+        game.unpause();
+        None
+    }
+
+    fn game_view(&self) -> GameView<'_> {
+        let game = self.game.as_ref().unwrap();
+        GameView {
+            game,
+            speed: self.args.settings.level(game.score()),
+            theme: &self.args.settings.theme,
+            mode: self.args.settings.render,
+            status: match (&self.playback, &self.bot) {
+                (Some(playback), _) => Some(playback.status(game)),
+                (None, Some(bot)) => Some(format!(" bot: {} ", bot.name())),
+                (None, None) => None,
+            },
+            space: self.frontend.size(),
+            camera: self.camera,
+            minimap: self.args.settings.minimap,
+        }
+    }
+
+    /// the current game in the middle of the screen.
+    /// also returns whether it fits, otherwise the screen only says it is too small.
+    fn game_screen(&self) -> (Frame, bool) {
+        let mut screen = self.blank_screen();
+        let view = self.game_view();
+        let fits = screen.blit_centered(&view.render());
+        if !fits {
+            draw_too_small(&mut screen, view.size());
+        } else if view.game.is_paused() && self.playback.is_none() {
+            let pause = PauseView { keys: &self.args.keys }.render();
+            let (x, y) = screen.center_of(pause.size());
+            screen.blit(&pause, x.max(0), y.max(0));
+        }
+        (screen, fits)
+    }
+
+    fn blank_screen(&self) -> Frame {
+        let (width, height) = self.frontend.size();
+        Frame::new(width, height)
+    }
 
-            start_color();
-            init_pair(1, COLOR_RED, COLOR_BLACK);
-            init_pair(2, COLOR_YELLOW, COLOR_BLACK);
-            init_pair(3, COLOR_GREEN, COLOR_BLACK);
-            init_pair(4, COLOR_WHITE, COLOR_BLACK);
+    fn write_record(&self) -> Result<(), String> {
+        if let Some(ref path) = self.args.record {
+            let replay = self.game.as_ref().unwrap().replay();
+            fs::write(path, replay.to_string())
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
         }
-        window.erase();
-        window.draw_box(0, 0);
-        window.refresh();
+        Ok(())
+    }
 
-        let width = window.get_max_x();
-        let height = window.get_max_y();
-        self.window =
-            Some(
-                Arc::new(
-                    Mutex::new(
-                        self.root_window
-                            .derwin(height - 2, width - 2, 1, 1)
-                            .unwrap()
-                            .into())));
+    /// ask whether to save the quit game, so it can be resumed later.
+    fn offer_save(&mut self, snapshot: &str) -> Result<(), String> {
+        let path = match paths::save_file() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        loop {
+            let (mut screen, _) = self.game_screen();
+            let (_, game_height) = self.game_view().size();
+            let below_game = (screen.height() + game_height) as isize / 2;
+            let row = below_game.min(screen.height() as isize - 1);
+            screen.print_centered(row, "Save game to resume later? (y/n)", Style::PLAIN);
+            self.frontend.show(&screen);
+            match self.frontend.read_key(None) {
+                Some(Key::Char('y')) => break,
+                Some(Key::Char('n')) | Some(Key::Char('q')) | Some(Key::Escape) => return Ok(()),
+                _ => (),
+            }
+        }
+        let text = args::saved_game_text(snapshot, self.played, self.assisted);
+        let write = || {
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(&path, text)
+        };
+        write().map_err(|e| format!("{}: {}", path.display(), e))
+    }
 
-        self.game = Some(Arc::new(Mutex::new(Game::new())));
+    /// set up a fresh game with current settings.
+    fn new_game(&mut self) {
+        let game = Game::with_config(self.args.settings.config.clone()).expect("config is validated");
+        self.game = Some(game);
+        self.playback = None;
+        self.played = Duration::ZERO;
+        self.assisted = false;
     }
 
-    fn tear_down(&self) {
-        let window = &self.root_window;
-        window.keypad(false);
-        window.nodelay(false);
+    /// set up playback of a recorded game.
+    fn start_replay(&mut self, replay: &Replay) {
+        let (game, player) = replay.play().expect("config is validated");
+        self.game = Some(game);
+        self.playback = Some(Playback::new(player));
     }
 }
 
-impl Drop for Application {
-    fn drop(&mut self) {
-        self.tear_down();
-        endwin();
+/// take over the terminal with given backend.
+fn open_frontend(backend: Backend) -> Result<Box<dyn Frontend>, String> {
+    match backend {
+        #[cfg(feature = "curses")]
+        Backend::Curses => Ok(Box::new(curses_frontend::CursesFrontend::new())),
+        #[cfg(feature = "crossterm")]
+        Backend::Crossterm => match crossterm_frontend::CrosstermFrontend::new() {
+            Ok(frontend) => Ok(Box::new(frontend)),
+            Err(e) => Err(format!("can not set up the terminal: {}", e)),
+        },
+        Backend::Plain => Ok(Box::new(plain_frontend::PlainFrontend::new())),
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("only available backends are accepted"),
     }
 }
 
 fn main() {
-    Application::new().start();
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    // application must be dropped to restore the terminal before reporting errors.
+    let result = open_frontend(args.backend).and_then(|frontend| Application::new(args, frontend).start());
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
 }
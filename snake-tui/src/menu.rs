@@ -0,0 +1,106 @@
+use snake::*;
+
+/// Vertical list of items in a bordered box, one of which is selected.
+pub struct Menu {
+    title: String,
+    items: Vec<String>,
+    selected: usize,
+}
+
+/// What the user did with a menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuInput {
+    /// item with given index was chosen with Enter or its hotkey.
+    Chosen(usize),
+    /// Left or Right arrow on the selected item, as -1 or +1.
+    Adjusted(usize, i32),
+    /// menu was dismissed with Escape.
+    Cancelled,
+    /// terminal was resized while the menu was shown, so it was closed
+    /// to let the screen behind it be laid out again.
+    Resized,
+}
+
+impl Menu {
+    pub fn new<S: Into<String>>(title: S, items: Vec<String>) -> Self {
+        Menu {
+            title: title.into(),
+            items,
+            selected: 0,
+        }
+    }
+
+    pub fn set_title<S: Into<String>>(&mut self, title: S) {
+        self.title = title.into();
+    }
+
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.selected = self.selected.min(items.len().saturating_sub(1));
+        self.items = items;
+    }
+
+    /// handle a key press.  items can also be chosen by the first letter of their label.
+    pub fn input(&mut self, key: Key) -> Option<MenuInput> {
+        match key {
+            Key::Up | Key::Char('k') => {
+                self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+            },
+            Key::Down | Key::Char('j') => {
+                self.selected = (self.selected + 1) % self.items.len();
+            },
+            Key::Left => return Some(MenuInput::Adjusted(self.selected, -1)),
+            Key::Right => return Some(MenuInput::Adjusted(self.selected, 1)),
+            Key::Enter | Key::Char(' ') => return Some(MenuInput::Chosen(self.selected)),
+            Key::Escape => return Some(MenuInput::Cancelled),
+            Key::Resize => return Some(MenuInput::Resized),
+            Key::Char(c) => {
+                let c = c.to_ascii_lowercase();
+                let hotkey = self.items.iter().position(|item| {
+                    item.chars().next().map(|first| first.to_ascii_lowercase()) == Some(c)
+                });
+                if let Some(index) = hotkey {
+                    self.selected = index;
+                    return Some(MenuInput::Chosen(index));
+                }
+            },
+            _ => (),
+        }
+        None
+    }
+
+    /// show the menu centered over `background` and wait until the user does something with it.
+    pub fn run(&mut self, frontend: &mut dyn Frontend, background: &Frame) -> MenuInput {
+        loop {
+            let mut screen = background.clone();
+            let menu = self.render();
+            let (x, y) = screen.center_of(menu.size());
+            screen.blit(&menu, x.max(0), y.max(0));
+            frontend.show(&screen);
+            if let Some(input) = frontend.read_key(None).and_then(|key| self.input(key)) {
+                return input;
+            }
+        }
+    }
+}
+
+impl Widget for Menu {
+    fn size(&self) -> (usize, usize) {
+        let longest = self.items
+            .iter()
+            .map(|item| item.chars().count())
+            .chain(Some(self.title.chars().count()))
+            .max()
+            .unwrap_or(0);
+        // border, title and an empty line above the items.
+        (longest + 6, self.items.len() + 4)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        frame.draw_border(&Border::LINE, Style::PLAIN);
+        frame.print_centered(1, &self.title, Style::PLAIN.bold());
+        for (i, item) in self.items.iter().enumerate() {
+            let style = if i == self.selected { Style::PLAIN.reverse() } else { Style::PLAIN };
+            frame.print(2, 3 + i as isize, &format!(" {} ", item), style);
+        }
+    }
+}
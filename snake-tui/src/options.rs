@@ -0,0 +1,94 @@
+use snake::*;
+
+use crate::menu::{Menu, MenuInput};
+use crate::settings::{Settings, SPEEDS};
+use crate::theme::Theme;
+
+const WIDTHS: (usize, usize) = (5, 200);
+const HEIGHTS: (usize, usize) = (3, 100);
+
+/// Screen for changing the settings of the next game.
+pub struct Options {
+    settings: Settings,
+    /// themes to choose from.
+    themes: Vec<Theme>,
+    menu: Menu,
+}
+
+impl Options {
+    pub fn new(settings: Settings, themes: Vec<Theme>) -> Self {
+        let mut options = Options {
+            settings,
+            themes,
+            menu: Menu::new("Options", Vec::new()),
+        };
+        options.update_items();
+        options
+    }
+
+    fn update_items(&mut self) {
+        let settings = &self.settings;
+        self.menu.set_items(vec![
+            format!("Width: {:>3}", settings.config.width),
+            format!("Height: {:>3}", settings.config.height),
+            format!("Speed: {:>2}", settings.speed),
+            format!("Accelerate: {}", if settings.accelerate { "on" } else { "off" }),
+            format!("Borders: {}", match settings.config.topology {
+                Topology::Wrapping => "wrap",
+                Topology::Walled => "walls",
+            }),
+            format!("Theme: {}", settings.theme.name),
+            format!("Cells: {}", settings.render.name()),
+            format!("Minimap: {}", if settings.minimap { "on" } else { "off" }),
+            "Done".to_string(),
+        ]);
+    }
+
+    fn adjust(&mut self, item: usize, delta: i32) {
+        let step = |value: usize, (min, max): (usize, usize)| {
+            (value as i32 + delta).max(min as i32).min(max as i32) as usize
+        };
+        let settings = &mut self.settings;
+        let config = &mut settings.config;
+        match item {
+            // a level has its own size, so resizing the field drops it.
+            0 => { config.width = step(config.width, WIDTHS); config.map = None; config.start = None; },
+            1 => { config.height = step(config.height, HEIGHTS); config.map = None; config.start = None; },
+            2 => settings.speed = step(settings.speed as usize, (SPEEDS.0 as usize, SPEEDS.1 as usize)) as u32,
+            3 => settings.accelerate = !settings.accelerate,
+            4 => config.topology = match config.topology {
+                Topology::Wrapping => Topology::Walled,
+                Topology::Walled => Topology::Wrapping,
+            },
+            5 => {
+                let n = self.themes.len() as i32;
+                let i = self.themes.iter().position(|theme| theme.name == settings.theme.name).unwrap_or(0) as i32;
+                settings.theme = self.themes[(i + delta).rem_euclid(n) as usize].clone();
+            },
+            6 => settings.render = settings.render.cycle(delta),
+            7 => settings.minimap = !settings.minimap,
+            _ => (),
+        }
+    }
+
+    /// let the user edit the settings.
+    /// returns the new ones, or `None` if the screen was cancelled.
+    pub fn run(mut self, frontend: &mut dyn Frontend) -> Option<Settings> {
+        loop {
+            self.update_items();
+            let (width, height) = frontend.size();
+            let input = self.menu.run(frontend, &Frame::new(width, height));
+            self.menu.set_title("Options");
+            match input {
+                MenuInput::Adjusted(item, delta) => self.adjust(item, delta),
+                MenuInput::Chosen(8) => match self.settings.config.validate() {
+                    Ok(()) => return Some(self.settings),
+                    Err(e) => self.menu.set_title(e.to_string()),
+                },
+                MenuInput::Chosen(item) => self.adjust(item, 1),
+                MenuInput::Cancelled => return None,
+                MenuInput::Resized => (),
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::env;
+use std::path::PathBuf;
+
+use snake::*;
+
+/// directory for game data, as specified by XDG Base Directory Specification.
+pub fn data_dir() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|dir| dir.join("snake-rs"))
+}
+
+/// directory for settings written by the player, as specified by XDG Base Directory Specification.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("snake-rs"))
+}
+
+/// key bindings, see `keys::load`.
+pub fn keys_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("keys.toml"))
+}
+
+/// directory of theme files, see `Theme::load_all`.
+pub fn themes_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("themes"))
+}
+
+/// where a game is saved on quit, and resumed from with `--resume`.
+pub fn save_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("saved-game"))
+}
+
+/// high score table for games played with given config.
+pub fn scores_file(config: &GameConfig) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("scores").join(board_name(config)))
+}
@@ -0,0 +1,255 @@
+use std::env;
+use std::io::{self, IsTerminal, Read, Stdout, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use snake::*;
+
+/// how long to wait for the rest of an escape sequence.
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Frontend which needs nothing but the standard library.
+///
+/// On a terminal, frames are redrawn in place with ANSI escapes, and keys are read
+/// one by one after switching the terminal to raw mode with `stty`.
+/// Otherwise, e.g. when piped into a log, every new frame is printed as plain text
+/// and keys are read from whatever stdin is.
+pub struct PlainFrontend {
+    stdout: Stdout,
+    /// whether output goes to a terminal rather than a pipe or file.
+    interactive: bool,
+    size: (usize, usize),
+    /// bytes from stdin, read by a thread of their own, since std can not
+    /// wait for input with a timeout.
+    input: Receiver<u8>,
+    /// terminal settings to restore, if they were changed.
+    saved_tty: Option<String>,
+    /// last frame shown, to only draw what changed.
+    shown: Option<Frame>,
+}
+
+impl PlainFrontend {
+    pub fn new() -> Self {
+        let stdout = io::stdout();
+        let interactive = stdout.is_terminal();
+        let saved_tty = if io::stdin().is_terminal() {
+            let saved = stty(&["-g"]);
+            stty(&["raw", "-echo"]);
+            saved
+        } else {
+            None
+        };
+        let (tx, input) = channel();
+        thread::spawn(move || {
+            for byte in io::stdin().lock().bytes() {
+                match byte {
+                    Ok(byte) => if tx.send(byte).is_err() { break },
+                    Err(_) => break,
+                }
+            }
+        });
+        let mut frontend = PlainFrontend {
+            stdout,
+            interactive,
+            size: screen_size(interactive),
+            input,
+            saved_tty,
+            shown: None,
+        };
+        if interactive {
+            // alternate screen, and no cursor.
+            frontend.write("\x1b[?1049h\x1b[?25l\x1b[2J");
+        }
+        frontend
+    }
+
+    fn write(&mut self, text: &str) {
+        let _ = self.stdout.write_all(text.as_bytes());
+        let _ = self.stdout.flush();
+    }
+
+    /// redraw rows which changed since the last frame.
+    fn draw(&mut self, frame: &Frame) {
+        let previous = self.shown.as_ref().filter(|shown| shown.size() == frame.size());
+        let mut out = String::new();
+        for y in 0..frame.height() {
+            let row: Vec<StyledCell> = (0..frame.width()).map(|x| frame.get(x, y).unwrap()).collect();
+            let unchanged = previous.is_some_and(|shown| {
+                row.iter().enumerate().all(|(x, cell)| shown.get(x, y) == Some(*cell))
+            });
+            if unchanged { continue }
+            out.push_str(&format!("\x1b[{};1H", y + 1));
+            let mut style = None;
+            for cell in row.into_iter().filter(|cell| cell.ch != StyledCell::CONTINUATION) {
+                if style != Some(cell.style) {
+                    out.push_str(&sgr(cell.style));
+                    style = Some(cell.style);
+                }
+                out.push(cell.ch);
+            }
+            out.push_str("\x1b[0m");
+        }
+        self.write(&out);
+    }
+
+    /// print the part of the frame which is not blank, followed by an empty line.
+    fn print(&mut self, frame: &Frame) {
+        let text = frame.to_string();
+        let lines: Vec<&str> = text.lines().map(|line| line.trim_end()).collect();
+        let first = lines.iter().position(|line| !line.is_empty()).unwrap_or(0);
+        let last = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |i| i + 1);
+        let lines = &lines[first..last.max(first)];
+        let indent = lines
+            .iter()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let mut out = String::new();
+        for line in lines {
+            out.push_str(line.get(indent..).unwrap_or(""));
+            out.push('\n');
+        }
+        out.push('\n');
+        self.write(&out);
+    }
+
+    fn next_byte(&self) -> Option<u8> {
+        self.input.recv_timeout(ESCAPE_TIMEOUT).ok()
+    }
+
+    fn decode(&self, byte: u8) -> Option<Key> {
+        Some(match byte {
+            0x1b => match self.next_byte() {
+                Some(b'[') | Some(b'O') => match self.next_byte()? {
+                    b'A' => Key::Up,
+                    b'B' => Key::Down,
+                    b'C' => Key::Right,
+                    b'D' => Key::Left,
+                    _ => return None,
+                },
+                _ => Key::Escape,
+            },
+            b'\r' | b'\n' => Key::Enter,
+            0x7f | 0x08 => Key::Backspace,
+            // Ctrl-C, which raw mode delivers as a byte.
+            0x03 => Key::Escape,
+            byte if byte < 0x80 => Key::Char(byte as char),
+            byte => {
+                let len = byte.leading_ones() as usize;
+                let mut bytes = vec![byte];
+                for _ in 1..len {
+                    bytes.push(self.next_byte()?);
+                }
+                Key::Char(std::str::from_utf8(&bytes).ok()?.chars().next()?)
+            },
+        })
+    }
+}
+
+impl Drop for PlainFrontend {
+    fn drop(&mut self) {
+        if self.interactive {
+            self.write("\x1b[0m\x1b[?25h\x1b[?1049l");
+        }
+        if let Some(ref saved) = self.saved_tty {
+            stty(&[saved.as_str()]);
+        }
+    }
+}
+
+impl Frontend for PlainFrontend {
+    fn size(&self) -> (usize, usize) { self.size }
+
+    fn show(&mut self, frame: &Frame) {
+        if self.shown.as_ref() == Some(frame) {
+            return;
+        }
+        if self.interactive {
+            self.draw(frame);
+        } else {
+            self.print(frame);
+        }
+        self.shown = Some(frame.clone());
+    }
+
+    /// once stdin is closed, waiting forever gives Escape, to get out of menus and prompts.
+    fn read_key(&mut self, timeout: Option<Duration>) -> Option<Key> {
+        let byte = match timeout {
+            Some(timeout) => match self.input.recv_timeout(timeout) {
+                Ok(byte) => byte,
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    thread::sleep(timeout);
+                    return None;
+                },
+            },
+            None => match self.input.recv() {
+                Ok(byte) => byte,
+                Err(_) => return Some(Key::Escape),
+            },
+        };
+        self.decode(byte)
+    }
+}
+
+/// run `stty` on the terminal, which it finds on stdin.
+/// returns what it printed, if it succeeded.
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// size of the terminal as `(width, height)`, or a guess when there is none.
+fn screen_size(interactive: bool) -> (usize, usize) {
+    let from_stty = || {
+        let size = stty(&["size"])?;
+        let mut numbers = size.split_whitespace().map(|n| n.parse::<usize>().ok());
+        let (height, width) = (numbers.next()??, numbers.next()??);
+        Some((width, height))
+    };
+    let from_env = || {
+        let number = |name| env::var(name).ok()?.parse::<usize>().ok();
+        Some((number("COLUMNS")?, number("LINES")?))
+    };
+    let size = if interactive { from_stty().or_else(from_env) } else { from_env() };
+    size.filter(|&(width, height)| width > 0 && height > 0).unwrap_or((80, 24))
+}
+
+/// escape sequence switching to given style.
+fn sgr(style: Style) -> String {
+    let mut codes = String::from("\x1b[0");
+    if style.bold {
+        codes.push_str(";1");
+    }
+    if style.reverse {
+        codes.push_str(";7");
+    }
+    if let Some(n) = color_number(style.fg) {
+        codes.push_str(&format!(";3{}", n));
+    }
+    if let Some(n) = color_number(style.bg) {
+        codes.push_str(&format!(";4{}", n));
+    }
+    codes.push('m');
+    codes
+}
+
+fn color_number(color: Color) -> Option<u8> {
+    Some(match color {
+        Color::Default => return None,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::White => 7,
+    })
+}
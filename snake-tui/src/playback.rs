@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use snake::*;
+
+/// Replay being played back, with its playback controls.
+pub struct Playback {
+    player: ReplayPlayer,
+    speed: u32,
+    step_once: bool,
+}
+
+impl Playback {
+    pub fn new(player: ReplayPlayer) -> Self {
+        Playback {
+            player,
+            speed: 1,
+            step_once: false,
+        }
+    }
+
+    /// advance the replay by one frame of the render loop.
+    pub fn tick(&mut self, game: &mut Game) {
+        if game.is_paused() {
+            if self.step_once {
+                self.step_once = false;
+                game.unpause();
+                self.player.step(game);
+                game.pause();
+            }
+        } else {
+            self.player.step(game);
+        }
+        if self.player.is_finished(game) {
+            game.quit();
+        }
+    }
+
+    /// make a single step while paused.
+    pub fn step_forward(&mut self) { self.step_once = true; }
+
+    /// playback speed multiplier: 1, 2 or 4.
+    pub fn set_speed(&mut self, speed: u32) { self.speed = speed; }
+
+    /// time between frames at current speed.
+    pub fn delay(&self, base: Duration) -> Duration { base / self.speed }
+
+    /// progress and speed of the replay, to be shown with the game.
+    pub fn status(&self, game: &Game) -> String {
+        let status = if game.is_paused() { "paused".to_string() } else { format!("{}x", self.speed) };
+        format!(" replay {}/{} {} ", game.ticks(), self.player.replay().ticks(), status)
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use snake::*;
+
+use crate::snake_window::RenderMode;
+use crate::theme::Theme;
+
+/// slowest and fastest speed levels.
+pub const SPEEDS: (u32, u32) = (1, 12);
+/// with acceleration on, the speed goes up a level every this many points.
+const POINTS_PER_LEVEL: usize = 5;
+
+/// Everything the player can change between games.
+#[derive(Clone)]
+pub struct Settings {
+    pub config: GameConfig,
+    /// how fast the snake moves, from `SPEEDS.0` to `SPEEDS.1`.
+    pub speed: u32,
+    /// whether the game speeds up as the score grows.
+    pub accelerate: bool,
+    pub theme: Theme,
+    pub render: RenderMode,
+    /// cells kept between the head and the edges of a field which scrolls, see `Camera`.
+    pub margin: Option<usize>,
+    /// whether a field which scrolls has a map of the whole of it in a corner.
+    pub minimap: bool,
+    /// bot the autopilot key hands the game to, one of `BOTS`.
+    pub bot: &'static str,
+    /// whether games start with the bot playing.
+    pub autopilot: bool,
+}
+
+impl Settings {
+    pub fn new(config: GameConfig) -> Self {
+        Settings {
+            config,
+            speed: 6,
+            accelerate: false,
+            theme: Theme::default(),
+            render: RenderMode::default(),
+            margin: None,
+            minimap: true,
+            bot: "safe",
+            autopilot: false,
+        }
+    }
+
+    /// speed level once the game has reached given score.
+    pub fn level(&self, score: usize) -> u32 {
+        let bonus = if self.accelerate { score / POINTS_PER_LEVEL } else { 0 };
+        (self.speed as usize + bonus).min(SPEEDS.1 as usize) as u32
+    }
+
+    /// time between two moves of the snake at given score.
+    /// each level is a fifth faster than the one before, starting at 600ms.
+    pub fn tick(&self, score: usize) -> Duration {
+        let ms = 600.0 * 0.8f64.powi(self.level(score) as i32 - 1);
+        Duration::from_millis(ms as u64)
+    }
+}
@@ -1,101 +1,656 @@
-use pancurses::{self, Window, COLOR_PAIR};
+use std::time::Duration;
 
 use snake::*;
 
-use crate::curses_window::CursesWindow;
+use crate::menu::MenuInput;
+use crate::theme::Theme;
 
-impl CursesWindow for Game {
-    fn win_size(&self) -> (i32, i32) {
-        let view: FieldView = self.into();
-        let (height, width) = view.win_size();
-        (height + 2 + 1, width + 2) // 2 for border and 1 for header
+/// Game as shown while playing: header with score and speed above the field.
+pub struct GameView<'a> {
+    pub game: &'a Game,
+    /// current speed level.
+    pub speed: u32,
+    pub theme: &'a Theme,
+    pub mode: RenderMode,
+    /// line shown over the bottom border, e.g. replay progress.
+    pub status: Option<String>,
+    /// room there is for the view.  a field which does not fit scrolls.
+    pub space: (usize, usize),
+    /// where a field which scrolls is in view.
+    pub camera: Camera,
+    /// whether a map of the whole field goes in a corner of one which scrolls.
+    pub minimap: bool,
+}
+
+impl<'a> GameView<'a> {
+    /// field as shown, moving `camera` along with the head if it scrolls.
+    fn field_view(&self, camera: &mut Camera) -> FieldView<'a> {
+        // border and header take two columns and three lines.
+        let space = (self.space.0.saturating_sub(2), self.space.1.saturating_sub(3));
+        FieldView::new(self.game, self.theme, self.mode).scroll(space, camera)
+    }
+
+    /// camera as it is once it has followed the head, to keep for the next frame.
+    pub fn follow(&self) -> Camera {
+        let mut camera = self.camera;
+        self.field_view(&mut camera);
+        camera
+    }
+}
+
+impl<'a> Widget for GameView<'a> {
+    fn size(&self) -> (usize, usize) {
+        let (width, height) = self.field_view(&mut self.camera.clone()).size();
+        (width + 2, height + 2 + 1) // 2 for border and 1 for header
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let game = self.game;
+        frame.draw_border(&self.theme.border, self.theme.colors.border);
+        match game.state() {
+            GameState::GameOver => match game.death_cause() {
+                Some(DeathCause::Wall) | Some(DeathCause::Obstacle) => frame.print(5, 0, "Hit the wall!", Style::PLAIN),
+                _ => frame.print(7, 0, "Game over!", Style::PLAIN),
+            },
+            GameState::Won => frame.print(8, 0, "You won!", Style::PLAIN),
+            _ => (),
+        };
+        let score = format!("Score: {}", game.score());
+        frame.print(2, 1, &score, Style::PLAIN);
+        // speed goes to the right, if there is room next to the score.
+        let speed = format!("Speed: {}", self.speed);
+        let column = frame.width() as isize - 2 - speed.len() as isize;
+        if column > 2 + score.len() as isize {
+            frame.print(column, 1, &speed, Style::PLAIN);
+        }
+
+        let view = self.field_view(&mut self.camera.clone());
+        let mut field = view.render();
+        if self.minimap && view.is_scrolled() {
+            let inside = (field.width() - 2, field.height() - 2);
+            if let Some(minimap) = Minimap::new(&view, (inside.0 / 3, inside.1 / 3)) {
+                // the map goes in the corner on the right across from the head, so it never hides it.
+                let (width, height) = minimap.size();
+                let y = if view.head_row() < view.cells.1 / 2 { field.height() - height } else { 0 };
+                field.blit(&minimap.render(), (field.width() - width) as isize, y as isize);
+            }
+        }
+        if matches!(game.state(), GameState::Won) {
+            draw_victory(&mut field, game, self.theme);
+        }
+        frame.blit(&field, 1, 2);
+
+        if let Some(ref status) = self.status {
+            frame.print(1, frame.height() as isize - 1, status, Style::PLAIN);
+        }
+    }
+}
+
+/// banner over the field, which is entirely covered by the snake by now.
+fn draw_victory(frame: &mut Frame, game: &Game, theme: &Theme) {
+    let lines = [
+        "  YOU WON!  ".to_string(),
+        format!(" length {} ", game.snake_len()),
+    ];
+    let top = (frame.height() as isize - lines.len() as isize) / 2;
+    for (i, line) in lines.iter().enumerate() {
+        frame.print_centered(top + i as isize, line, theme.colors.banner);
+    }
+}
+
+/// notice shown instead of a game which does not fit on the screen.
+pub fn draw_too_small(frame: &mut Frame, (width, height): (usize, usize)) {
+    let lines = [
+        "Terminal too small".to_string(),
+        format!("need {}x{}, have {}x{}", width, height, frame.width(), frame.height()),
+    ];
+    let top = (frame.height() as isize - lines.len() as isize) / 2;
+    for (i, line) in lines.iter().enumerate() {
+        frame.print_centered(top + i as isize, line, Style::PLAIN);
+    }
+}
+
+/// Box shown over a paused game, listing what keys do.
+pub struct PauseView<'a> {
+    pub keys: &'a KeyBindings,
+}
+
+impl<'a> PauseView<'a> {
+    /// one line per action with keys bound to it.
+    fn lines(&self) -> Vec<String> {
+        Action::ALL
+            .iter()
+            .filter_map(|&action| {
+                let keys: Vec<String> = self.keys.keys(action).map(Key::name).collect();
+                if keys.is_empty() {
+                    return None;
+                }
+                Some(format!("{:<10} {}", action.name(), keys.join(" ")))
+            })
+            .collect()
+    }
+}
+
+impl<'a> Widget for PauseView<'a> {
+    fn size(&self) -> (usize, usize) {
+        let lines = self.lines();
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0).max(6);
+        // border, title and an empty line above the list.
+        (width + 4, lines.len() + 4)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        frame.draw_border(&Border::LINE, Style::PLAIN);
+        frame.print_centered(1, "Paused", Style::PLAIN.bold());
+        for (i, line) in self.lines().iter().enumerate() {
+            frame.print(2, 3 + i as isize, line, Style::PLAIN);
+        }
+    }
+}
+
+/// longest name that goes next to a high score.
+pub const NAME_LEN: usize = 16;
+
+/// Box asking for the name to put next to a new high score.
+pub struct NameEntry {
+    /// place the score takes in the table.
+    pub rank: usize,
+    pub name: String,
+}
+
+impl NameEntry {
+    /// handle a key press.  the name is `Chosen` with Enter, or `Cancelled` with Escape
+    /// to leave the score out of the table.
+    pub fn input(&mut self, key: Key) -> Option<MenuInput> {
+        match key {
+            Key::Enter if !self.name.trim().is_empty() => return Some(MenuInput::Chosen(0)),
+            Key::Escape => return Some(MenuInput::Cancelled),
+            Key::Resize => return Some(MenuInput::Resized),
+            Key::Backspace => { self.name.pop(); },
+            Key::Char(c) if !c.is_control() && self.name.chars().count() < NAME_LEN => self.name.push(c),
+            _ => (),
+        }
+        None
+    }
+
+    /// show the box centered over `background` until the name is entered or cancelled.
+    pub fn run(&mut self, frontend: &mut dyn Frontend, background: &Frame) -> MenuInput {
+        loop {
+            let mut screen = background.clone();
+            let entry = self.render();
+            let (x, y) = screen.center_of(entry.size());
+            screen.blit(&entry, x.max(0), y.max(0));
+            frontend.show(&screen);
+            if let Some(input) = frontend.read_key(None).and_then(|key| self.input(key)) {
+                return input;
+            }
+        }
+    }
+}
+
+impl Widget for NameEntry {
+    fn size(&self) -> (usize, usize) {
+        (NAME_LEN + 11, 5)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        frame.draw_border(&Border::LINE, Style::PLAIN);
+        frame.print_centered(1, &format!("New high score: #{}", self.rank + 1), Style::PLAIN.bold());
+        frame.print(2, 3, "Name:", Style::PLAIN);
+        let len = self.name.chars().count();
+        frame.print(8, 3, &self.name, Style::PLAIN);
+        frame.print(8 + len as isize, 3, &"_".repeat(NAME_LEN - len), Style::PLAIN);
+        // cursor
+        if len < NAME_LEN {
+            frame.put(8 + len as isize, 3, ' ', Style::PLAIN.reverse());
+        }
+    }
+}
+
+/// Table of the best games played on one kind of board.
+pub struct ScoresView<'a> {
+    pub board: &'a str,
+    pub table: &'a ScoreTable,
+    /// place of a game to point out, e.g. the one just played.
+    pub highlight: Option<usize>,
+}
+
+impl<'a> ScoresView<'a> {
+    fn row(&self, rank: usize, entry: &HighScore) -> String {
+        let name: String = entry.name.chars().take(NAME_LEN).collect();
+        format!("{:>2}. {:<name_len$} {:>5} {:>6} {:>7}  {}",
+                rank + 1, name, entry.score, entry.length,
+                format_duration(entry.duration), format_date(entry.date),
+                name_len = NAME_LEN)
     }
+}
 
-    fn draw(&self, window: &Window) {
-        window.clear();
-        window.draw_box(0, 0);
-        if matches!(self.state(), GameState::GameOver) {
-            window.mvprintw(0, 7, "Game over!");
+impl<'a> Widget for ScoresView<'a> {
+    fn size(&self) -> (usize, usize) {
+        // rank, name, score, length, time and date columns.
+        let width = 4 + NAME_LEN + 6 + 7 + 8 + 12;
+        let rows = self.table.entries().len().max(1);
+        // border, title and header with an empty line between, and a hint at the bottom.
+        (width + 4, rows + 7)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        frame.draw_border(&Border::LINE, Style::PLAIN);
+        frame.print_centered(1, &format!("High scores: {}", self.board), Style::PLAIN.bold());
+        let header = format!("{:>2}  {:<name_len$} {:>5} {:>6} {:>7}  {}",
+                             "#", "Name", "Score", "Length", "Time", "Date", name_len = NAME_LEN);
+        frame.print(2, 3, &header, Style::PLAIN.bold());
+        for (i, entry) in self.table.entries().iter().enumerate() {
+            let style = if self.highlight == Some(i) { Style::PLAIN.reverse() } else { Style::PLAIN };
+            frame.print(2, 4 + i as isize, &self.row(i, entry), style);
+        }
+        if self.table.entries().is_empty() {
+            frame.print_centered(4, "No games yet", Style::PLAIN);
         }
-        window.mvprintw(1, 8, &format!("Score: {}", self.score()));
-        // field's sub-window
-        {
-            let view: FieldView = self.into();
-            let (height, width) = view.win_size();
-            let sub_window = window.derwin(height, width, 2, 1).unwrap();
-            view.draw(&sub_window);
-            sub_window.delwin();
+        frame.print_centered(frame.height() as isize - 2, "Press any key", Style::PLAIN);
+    }
+}
+
+/// duration as `m:ss`, or `h:mm:ss` for long games.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// date as `yyyy-mm-dd` in UTC, given in seconds since the Unix epoch.
+fn format_date(timestamp: u64) -> String {
+    // days to civil date, from Howard Hinnant's `civil_from_days`.
+    let days = (timestamp / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// How field cells are drawn, since terminal cells are about twice as tall as they are wide.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// a character per cell, which makes the field look stretched.
+    #[default]
+    Normal,
+    /// two columns per cell, so cells are about square.
+    Double,
+    /// two cells per character, one above the other, drawn with half blocks in the colors
+    /// of the theme.  cells are about square, and glyphs are not shown.
+    HalfBlock,
+}
+
+impl RenderMode {
+    pub const ALL: [RenderMode; 3] = [RenderMode::Normal, RenderMode::Double, RenderMode::HalfBlock];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Normal => "normal",
+            RenderMode::Double => "double",
+            RenderMode::HalfBlock => "half-block",
         }
     }
+
+    pub fn from_name(name: &str) -> Option<RenderMode> {
+        RenderMode::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+
+    /// mode `delta` places away in `ALL`, going around.
+    pub fn cycle(self, delta: i32) -> RenderMode {
+        let n = RenderMode::ALL.len() as i32;
+        let i = RenderMode::ALL.iter().position(|&m| m == self).unwrap() as i32;
+        RenderMode::ALL[(i + delta).rem_euclid(n) as usize]
+    }
 }
 
+/// What a single field cell shows.
+#[derive(Clone, Copy)]
+struct Glyph {
+    ch: char,
+    style: Style,
+    /// what goes between this cell and the one to its right, when cells are two columns wide.
+    /// lines and walls go on, anything else is followed by a space.
+    fill: char,
+}
+
+/// fewest cells across and down shown of a field which scrolls.
+const MIN_VIEW: (usize, usize) = (10, 6);
+
 struct FieldView<'a> {
     field: &'a Field,
-    head: Point<isize>,
-    tail: Point<isize>,
+    /// parts of the snake, from the head.
+    segments: Vec<(Point<isize>, Segment)>,
+    topology: Topology,
+    theme: &'a Theme,
+    mode: RenderMode,
+    /// cell shown at the top left, counting the ring of walls if there is one.
+    origin: Point<isize>,
+    /// cells shown across and down.
+    cells: (usize, usize),
+    /// whether walls are drawn around the field as cells of it.  when a walled field scrolls
+    /// its border stands for the edge of the view, so the walls have to scroll along.
+    ring: bool,
 }
 
 impl<'a> FieldView<'a> {
-    pub fn new(field: &'a Field, head: Point<isize>, tail: Point<isize>) -> Self {
+    pub fn new(game: &'a Game, theme: &'a Theme, mode: RenderMode) -> Self {
         Self {
-            field,
-            head,
-            tail
+            field: game.field(),
+            segments: game.segments(),
+            topology: game.config().topology,
+            theme,
+            mode,
+            origin: Point { x: 0, y: 0 },
+            cells: game.field().size(),
+            ring: false,
+        }
+    }
+
+    /// show only the cells which fit in `space` if the whole field does not,
+    /// with the view moved by `camera` to follow the head.
+    fn scroll(mut self, space: (usize, usize), camera: &mut Camera) -> Self {
+        let (width, height) = self.size();
+        if width <= space.0 && height <= space.1 {
+            return self;
+        }
+        self.ring = self.topology == Topology::Walled;
+        let ring = self.ring as usize;
+        let (width, height) = self.field.size();
+        let size = (width + 2 * ring, height + 2 * ring);
+        let lines = if self.mode == RenderMode::HalfBlock { 2 } else { 1 };
+        let fit = (space.0.saturating_sub(2) / self.columns(), space.1.saturating_sub(2) * lines);
+        self.cells = (fit.0.max(MIN_VIEW.0).min(size.0), fit.1.max(MIN_VIEW.1).min(size.1));
+        let (head, _) = self.segments[0];
+        let target = Point { x: head.x + ring as isize, y: head.y + ring as isize };
+        self.origin = camera.follow(target, self.cells, size, self.topology == Topology::Wrapping);
+        self
+    }
+
+    /// whether only part of the field is shown.
+    fn is_scrolled(&self) -> bool {
+        self.ring || self.cells != self.field.size()
+    }
+
+    /// row of the view the head is in.
+    fn head_row(&self) -> usize {
+        let (head, _) = self.segments[0];
+        let (_, height) = self.field.size();
+        let ring = self.ring as isize;
+        let row = head.y + ring - self.origin.y;
+        match self.topology {
+            Topology::Wrapping => row.rem_euclid(height as isize) as usize,
+            Topology::Walled => row as usize,
+        }
+    }
+
+    /// field point shown in given cell of the view, which is outside the field for the ring.
+    fn point(&self, x: usize, y: usize) -> Point<isize> {
+        let ring = self.ring as isize;
+        let p = Point { x: self.origin.x + x as isize - ring, y: self.origin.y + y as isize - ring };
+        match self.topology {
+            Topology::Wrapping => p.wrap(&self.field.size()),
+            Topology::Walled => p,
+        }
+    }
+
+    /// glyphs of every cell of the field, by row.  empty cells have none.
+    fn glyphs(&self) -> Vec<Vec<Option<Glyph>>> {
+        let theme = self.theme;
+        let mut glyphs: Vec<Vec<Option<Glyph>>> = self.field
+            .rows()
+            .iter()
+            .map(|row| row.iter().map(|cell| match *cell {
+                Cell::Food => Some(Glyph { ch: theme.food, style: self.style(theme.colors.food, Color::Magenta), fill: ' ' }),
+                Cell::Wall => Some(self.wall()),
+                Cell::Rock => Some(Glyph { ch: theme.rock, style: self.style(theme.colors.rock, Color::Cyan), fill: ' ' }),
+                // snake parts are filled in below
+                Cell::Snake(_) | Cell::Empty => None,
+            }).collect())
+            .collect();
+        let colors = &theme.colors;
+        let mut body_styles = colors.body.iter().cycle();
+        let line = theme.segment(Segment::Straight(Right));
+        for &(p, segment) in &self.segments {
+            let style = match segment {
+                Segment::Head(_) => self.style(colors.head, Color::White),
+                Segment::Tail(_) => self.style(colors.tail, Color::Green),
+                _ => self.style(*body_styles.next().unwrap(), Color::Green),
+            };
+            let (behind, ahead) = segment.sides();
+            let fill = if behind == Some(Right) || ahead == Some(Right) { line } else { ' ' };
+            glyphs[p.y as usize][p.x as usize] = Some(Glyph { ch: theme.segment(segment), style, fill });
+        }
+        glyphs
+    }
+
+    /// style of a part of the field.  half blocks show nothing but the foreground color,
+    /// so parts which have none get `fallback`, for food, walls and rocks to look different.
+    fn style(&self, style: Style, fallback: Color) -> Style {
+        match style.fg {
+            Color::Default if self.mode == RenderMode::HalfBlock => Style { fg: fallback, ..style },
+            _ => style,
+        }
+    }
+
+    fn wall(&self) -> Glyph {
+        let theme = self.theme;
+        Glyph { ch: theme.wall, style: self.style(theme.colors.wall, Color::Blue), fill: theme.wall }
+    }
+
+    /// glyphs of the cells in view, by row.
+    fn visible(&self) -> Vec<Vec<Option<Glyph>>> {
+        let glyphs = self.glyphs();
+        let wall = self.wall();
+        (0..self.cells.1)
+            .map(|y| (0..self.cells.0).map(|x| {
+                let p = self.point(x, y);
+                if p.is_inside(&self.field.size()) { glyphs[p.y as usize][p.x as usize] } else { Some(wall) }
+            }).collect())
+            .collect()
+    }
+
+    /// columns each cell takes.
+    fn columns(&self) -> usize {
+        match self.mode {
+            RenderMode::HalfBlock => 1,
+            _ if self.theme.wide => 2,
+            RenderMode::Double => 2,
+            RenderMode::Normal => 1,
         }
     }
 }
 
-impl<'a> From<&'a Game> for FieldView<'a> {
-    fn from(game: &'a Game) -> Self {
-        Self::new(game.field(), game.head(), game.tail())
+/// character showing two cells, one above the other, in their colors.
+fn half_blocks(top: Option<Glyph>, bottom: Option<Glyph>) -> (char, Style) {
+    match (top.map(|glyph| glyph.style.fg), bottom.map(|glyph| glyph.style.fg)) {
+        (None, None) => (' ', Style::PLAIN),
+        (Some(top), None) => ('▀', Style::fg(top)),
+        (None, Some(bottom)) => ('▄', Style::fg(bottom)),
+        (Some(top), Some(bottom)) if top == bottom => ('█', Style::fg(top)),
+        // the default foreground has no background color to match, so it goes in front.
+        (Some(top), Some(Color::Default)) => ('▄', Style { bg: top, ..Style::PLAIN }),
+        (Some(top), Some(bottom)) => ('▀', Style { bg: bottom, ..Style::fg(top) }),
     }
 }
 
-impl<'a> CursesWindow for FieldView<'a> {
-    fn win_size(&self) -> (i32, i32) {
-        let (width, height) = self.field.size();
-        (height as i32 + 2, width as i32 + 2)
+impl<'a> Widget for FieldView<'a> {
+    fn size(&self) -> (usize, usize) {
+        let (width, height) = self.cells;
+        let rows = match self.mode {
+            RenderMode::HalfBlock => height.div_ceil(2),
+            _ => height,
+        };
+        (width * self.columns() + 2, rows + 2)
     }
 
-    fn draw(&self, window: &Window) {
-        window.clear();
-        window.draw_box(0, 0);
+    fn draw(&self, frame: &mut Frame) {
+        let theme = self.theme;
+        match self.topology {
+            Topology::Walled if !self.ring => frame.draw_border(&theme.walls, theme.colors.wall),
+            _ => frame.draw_border(&theme.border, theme.colors.border),
+        }
 
-        for (y, row) in self.field.rows().iter().enumerate() {
-            for (x, cell) in row.iter().enumerate() {
-                // snake parts are drawn separately
-                if !matches!(*cell, Cell::Snake(_)) {
-                    window.mvprintw(1+y as i32, 1+x as i32, &format!("{}", cell));
+        let glyphs = self.visible();
+        if self.mode == RenderMode::HalfBlock {
+            for (y, rows) in glyphs.chunks(2).enumerate() {
+                for x in 0..rows[0].len() {
+                    let bottom = rows.get(1).and_then(|row| row[x]);
+                    let (ch, style) = half_blocks(rows[0][x], bottom);
+                    frame.put(1 + x as isize, 1 + y as isize, ch, style);
                 }
             }
+            return;
         }
-        // now it's snake time, from tail to head.
-        let snake = {
-            let mut it = Vec::new();
-            let mut p = self.tail.clone();
-            it.push(p);
-            loop {
-                // prevent infinite loop
-                if p == self.head { break; }
-                // safely unwrap because we are not yet at the head
-                p = self.field.next_point(p).expect("no snake part here");
-                it.push(p);
+        for (y, row) in glyphs.iter().enumerate() {
+            for (x, glyph) in row.iter().enumerate() {
+                let glyph = match glyph {
+                    Some(glyph) => glyph,
+                    None => continue,
+                };
+                let (x, y) = (x as isize, 1 + y as isize);
+                if self.theme.wide {
+                    frame.put_wide(1 + 2 * x, y, glyph.ch, glyph.style);
+                } else if self.columns() == 2 {
+                    frame.put(1 + 2 * x, y, glyph.ch, glyph.style);
+                    frame.put(2 + 2 * x, y, glyph.fill, glyph.style);
+                } else {
+                    frame.put(1 + x, y, glyph.ch, glyph.style);
+                }
+            }
+        }
+    }
+}
+
+/// Map of the whole field in the corner of one which scrolls, one character for a block of cells,
+/// with the part in view dotted.
+struct Minimap<'a> {
+    view: &'a FieldView<'a>,
+    /// cells across each character stands for, which is half of those down.
+    scale: usize,
+}
+
+impl<'a> Minimap<'a> {
+    /// map fitting in `max` characters, border included, if there is room for one.
+    fn new(view: &'a FieldView<'a>, max: (usize, usize)) -> Option<Self> {
+        if max.0 < 4 || max.1 < 3 {
+            return None;
+        }
+        let (width, height) = view.field.size();
+        let scale = width.div_ceil(max.0 - 2).max(height.div_ceil(2 * (max.1 - 2))).max(1);
+        Some(Minimap { view, scale })
+    }
+}
+
+impl<'a> Widget for Minimap<'a> {
+    fn size(&self) -> (usize, usize) {
+        let (width, height) = self.view.field.size();
+        (width.div_ceil(self.scale) + 2, height.div_ceil(2 * self.scale) + 2)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let theme = self.view.theme;
+        frame.draw_border(&theme.border, theme.colors.border);
+        // what each character shows, the most important thing in its block.
+        let blocks = (frame.width() - 2, frame.height() - 2);
+        let mut marks = vec![vec![(0, ' ', Style::PLAIN); blocks.0]; blocks.1];
+        let mut mark = |p: Point<isize>, rank: u8, ch: char, style: Style| {
+            let mark = &mut marks[p.y as usize / (2 * self.scale)][p.x as usize / self.scale];
+            if rank > mark.0 {
+                *mark = (rank, ch, style);
             }
-            it
         };
-        let colors = [
-            COLOR_PAIR(4),
-            COLOR_PAIR(3),
-            COLOR_PAIR(2) | pancurses::A_BOLD,
-            COLOR_PAIR(1),
-        ];
-        for (p, color) in snake.iter().rev().zip(colors.iter().cycle()) {
-            window.attron(*color);
-            window.mvprintw(1 + p.y as i32,
-                            1 + p.x as i32,
-                            &format!("{}", self.field[p]));
-            window.attroff(*color);
-        }
-        window.attroff(colors[0]);
-    }
-}
\ No newline at end of file
+        for y in 0..self.view.cells.1 {
+            for x in 0..self.view.cells.0 {
+                let p = self.view.point(x, y);
+                if p.is_inside(&self.view.field.size()) {
+                    mark(p, 1, '.', Style::PLAIN);
+                }
+            }
+        }
+        let colors = &theme.colors;
+        for (y, row) in self.view.field.rows().iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let p = Point { x: x as isize, y: y as isize };
+                match *cell {
+                    Cell::Wall | Cell::Rock => mark(p, 2, '#', colors.wall),
+                    Cell::Snake(_) => mark(p, 3, 'o', colors.body[0]),
+                    Cell::Food => mark(p, 4, '*', colors.food),
+                    Cell::Empty => (),
+                }
+            }
+        }
+        let (head, _) = self.view.segments[0];
+        mark(head, 5, '@', colors.head);
+        for (y, row) in marks.iter().enumerate() {
+            for (x, &(_, ch, style)) in row.iter().enumerate() {
+                frame.put(1 + x as isize, 1 + y as isize, ch, style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn field_view_size() {
+        let game = Game::with_config(GameConfig::new().size(7, 5)).unwrap();
+        let theme = Theme::unicode();
+        let size = |mode| FieldView::new(&game, &theme, mode).size();
+        // the border takes two columns and two lines.
+        assert_eq!(size(RenderMode::Normal), (9, 7));
+        assert_eq!(size(RenderMode::Double), (16, 7));
+        // the last line holds only the top half of a pair of rows.
+        assert_eq!(size(RenderMode::HalfBlock), (9, 5));
+
+        let game = Game::with_config(GameConfig::new().size(7, 6)).unwrap();
+        assert_eq!(FieldView::new(&game, &theme, RenderMode::HalfBlock).size(), (9, 5));
+
+        // wide glyphs take two columns in any mode but half blocks, which have none.
+        let theme = Theme::emoji();
+        let size = |mode| FieldView::new(&game, &theme, mode).size();
+        assert_eq!(size(RenderMode::Normal), (16, 8));
+        assert_eq!(size(RenderMode::Double), (16, 8));
+        assert_eq!(size(RenderMode::HalfBlock), (9, 5));
+    }
+
+    #[test]
+    fn half_block_roles() {
+        // the default theme has no colors for food and walls, which half blocks need.
+        let map = Map::parse("*  #\n    \n>>  ").unwrap();
+        let game = Game::with_config(GameConfig::new().map(map)).unwrap();
+        let theme = Theme::default();
+        let view = FieldView::new(&game, &theme, RenderMode::HalfBlock);
+        let mut frame = Frame::new(6, 4);
+        view.draw(&mut frame);
+        let (food, wall) = (frame.get(1, 1).unwrap(), frame.get(4, 1).unwrap());
+        assert_eq!(wall.ch, '▀');
+        assert_ne!(food.style, wall.style);
+    }
+
+    #[test]
+    fn half_block_glyphs() {
+        let glyph = |color| Some(Glyph { ch: '*', style: Style::fg(color).bold(), fill: ' ' });
+        assert_eq!(half_blocks(None, None), (' ', Style::PLAIN));
+        assert_eq!(half_blocks(glyph(Color::Red), None), ('▀', Style::fg(Color::Red)));
+        assert_eq!(half_blocks(None, glyph(Color::Red)), ('▄', Style::fg(Color::Red)));
+        assert_eq!(half_blocks(glyph(Color::Red), glyph(Color::Red)), ('█', Style::fg(Color::Red)));
+
+        // two colors take the foreground and the background.
+        assert_eq!(half_blocks(glyph(Color::Red), glyph(Color::Green)),
+                   ('▀', Style { bg: Color::Green, ..Style::fg(Color::Red) }));
+        assert_eq!(half_blocks(glyph(Color::Red), glyph(Color::Default)),
+                   ('▄', Style { bg: Color::Red, ..Style::PLAIN }));
+    }
+}
@@ -0,0 +1,500 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use toml::{Table, Value};
+
+use snake::*;
+
+use crate::paths;
+
+/// Glyphs and colors the game is drawn with.
+///
+/// besides the built-in themes, each file in the `themes` config directory is a theme
+/// named after the file, see `Theme::parse`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    /// glyphs are two columns wide, as emoji are, so the field is drawn twice as wide.
+    pub wide: bool,
+    /// snake glyphs by the direction of each segment, in the order up, down, left and right.
+    pub head: [char; 4],
+    /// straight parts of the body.
+    pub body: [char; 4],
+    pub tail: [char; 4],
+    /// parts of the body where the snake turned, in the order of `Border::corners`.
+    /// without them, turns are drawn as body glyphs going the new way.
+    pub corners: Option<[char; 4]>,
+    pub food: char,
+    pub wall: char,
+    pub rock: char,
+    /// edges of a field the snake goes through.
+    pub border: Border,
+    /// edges of a walled field.
+    pub walls: Border,
+    pub colors: Colors,
+}
+
+/// Style of each part of the game.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Colors {
+    pub head: Style,
+    /// repeated along the body, starting next to the head.
+    pub body: Vec<Style>,
+    pub tail: Style,
+    pub food: Style,
+    /// walls inside the field, and around it when it is walled.
+    pub wall: Style,
+    pub rock: Style,
+    pub border: Style,
+    /// banner shown when the game is won.
+    pub banner: Style,
+}
+
+const ARROWS: [char; 4] = ['▲', '▼', '◀', '▶'];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::unicode()
+    }
+}
+
+impl Theme {
+    /// box-drawing characters, with the snake as one connected line, and colorful.
+    pub fn unicode() -> Theme {
+        Theme {
+            name: "unicode".to_string(),
+            wide: false,
+            head: ARROWS,
+            body: ['┃', '┃', '━', '━'],
+            tail: ['╹', '╻', '╸', '╺'],
+            corners: Some(['┏', '┓', '┗', '┛']),
+            food: '*',
+            wall: '▒',
+            rock: 'o',
+            border: Border::LINE,
+            walls: Border::solid('▒'),
+            colors: Colors {
+                head: Style::fg(Color::White),
+                body: vec![
+                    Style::fg(Color::Green),
+                    Style::fg(Color::Yellow).bold(),
+                    Style::fg(Color::Red),
+                    Style::fg(Color::White),
+                ],
+                tail: Style::fg(Color::Red),
+                food: Style::PLAIN,
+                wall: Style::PLAIN,
+                rock: Style::PLAIN,
+                border: Style::PLAIN,
+                banner: Style::fg(Color::Yellow).bold().reverse(),
+            },
+        }
+    }
+
+    /// nothing but ASCII, for terminals without Unicode.
+    pub fn ascii() -> Theme {
+        Theme {
+            name: "ascii".to_string(),
+            head: ['^', 'v', '<', '>'],
+            body: ['|', '|', '-', '-'],
+            tail: ['o'; 4],
+            corners: Some(['+'; 4]),
+            wall: '#',
+            border: Border::ASCII,
+            walls: Border::solid('#'),
+            colors: Colors {
+                head: Style::PLAIN.bold(),
+                body: vec![Style::fg(Color::Green)],
+                tail: Style::fg(Color::Green),
+                ..Theme::mono().colors
+            },
+            ..Theme::unicode()
+        }
+    }
+
+    /// emoji, which take two columns each.
+    pub fn emoji() -> Theme {
+        Theme {
+            name: "emoji".to_string(),
+            wide: true,
+            head: ['🐍'; 4],
+            body: ['🟩'; 4],
+            tail: ['🟢'; 4],
+            corners: None,
+            food: '🍎',
+            wall: '🧱',
+            rock: '🪨',
+            walls: Border { horizontal: '━', vertical: '┃', corners: ['┏', '┓', '┗', '┛'] },
+            ..Theme::mono()
+        }
+    }
+
+    /// solid blocks in bold, easy to see on any background.
+    pub fn high_contrast() -> Theme {
+        let bright = Style::fg(Color::White).bold();
+        Theme {
+            name: "high-contrast".to_string(),
+            body: ['█'; 4],
+            tail: ['▓'; 4],
+            corners: None,
+            food: '●',
+            wall: '█',
+            rock: '◆',
+            walls: Border::solid('█'),
+            colors: Colors {
+                head: bright.reverse(),
+                body: vec![bright],
+                tail: bright,
+                food: Style::fg(Color::Yellow).bold(),
+                wall: bright,
+                rock: bright,
+                border: bright,
+                banner: bright.reverse(),
+            },
+            ..Theme::unicode()
+        }
+    }
+
+    /// blue and yellow, which stay apart for red-green color blindness.
+    pub fn colorblind() -> Theme {
+        Theme {
+            name: "colorblind".to_string(),
+            food: '●',
+            colors: Colors {
+                head: Style::fg(Color::White).bold(),
+                body: vec![Style::fg(Color::Blue).bold(), Style::fg(Color::Cyan)],
+                tail: Style::fg(Color::Blue),
+                food: Style::fg(Color::Yellow).bold(),
+                banner: Style::fg(Color::Yellow).bold().reverse(),
+                ..Theme::mono().colors
+            },
+            ..Theme::unicode()
+        }
+    }
+
+    /// no colors, for terminals where they are hard to tell apart.
+    pub fn mono() -> Theme {
+        Theme {
+            name: "mono".to_string(),
+            colors: Colors {
+                head: Style::PLAIN,
+                body: vec![Style::PLAIN],
+                tail: Style::PLAIN,
+                food: Style::PLAIN,
+                wall: Style::PLAIN,
+                rock: Style::PLAIN,
+                border: Style::PLAIN,
+                banner: Style::PLAIN.bold().reverse(),
+            },
+            ..Theme::unicode()
+        }
+    }
+
+    pub fn builtin() -> Vec<Theme> {
+        vec![
+            Theme::unicode(),
+            Theme::ascii(),
+            Theme::emoji(),
+            Theme::high_contrast(),
+            Theme::colorblind(),
+            Theme::mono(),
+        ]
+    }
+
+    /// built-in themes, and those in the config directory, which can replace built-in ones.
+    pub fn load_all() -> Result<Vec<Theme>, String> {
+        let mut themes = Theme::builtin();
+        let dir = match paths::themes_dir() {
+            Some(dir) => dir,
+            None => return Ok(themes),
+        };
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(themes),
+            Err(e) => return Err(format!("{}: {}", dir.display(), e)),
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let theme = Theme::load(&path, &themes)?;
+            match themes.iter_mut().find(|t| t.name == theme.name) {
+                Some(existing) => *existing = theme,
+                None => themes.push(theme),
+            }
+        }
+        Ok(themes)
+    }
+
+    /// theme in given file, named after it.  it can be based on one of `themes`.
+    pub fn load(path: &Path, themes: &[Theme]) -> Result<Theme, String> {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Theme::parse(&name, &text, themes))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// theme described in TOML, as changes to a base theme, `unicode` unless given:
+    ///
+    /// ```toml
+    /// base = "ascii"
+    /// wide = false
+    ///
+    /// [glyphs]
+    /// head = "@"         # one character, or four: up, down, left and right
+    /// food = "$"
+    /// border = "-|++++"  # one character, or six: horizontal, vertical and the corners
+    ///
+    /// [colors]
+    /// head = "white bold"
+    /// body = ["green", "yellow on blue"]
+    /// ```
+    pub fn parse(name: &str, text: &str, themes: &[Theme]) -> Result<Theme, String> {
+        let mut table: Table = text.parse().map_err(|e: toml::de::Error| {
+            let line = e.span().map_or(0, |span| text[..span.start].matches('\n').count() + 1);
+            format!("line {}: {}", line, e.message().trim().replace('\n', ", "))
+        })?;
+        let mut theme = match table.remove("base") {
+            None => Theme::unicode(),
+            Some(Value::String(base)) => themes
+                .iter()
+                .find(|theme| theme.name == base)
+                .cloned()
+                .ok_or_else(|| format!("unknown base theme {:?}", base))?,
+            Some(_) => return Err("base must be a string".to_string()),
+        };
+        theme.name = name.to_string();
+        if let Some(wide) = table.remove("wide") {
+            theme.wide = wide.as_bool().ok_or("wide must be true or false")?;
+        }
+        for (key, value) in section(&mut table, "glyphs")? {
+            let text = value.as_str().ok_or_else(|| format!("glyphs.{}: expected a string", key))?;
+            let chars: Vec<char> = text.chars().collect();
+            let error = |n| format!("glyphs.{}: expected 1 or {} characters", key, n);
+            let four = || match chars[..] {
+                [c] => Ok([c; 4]),
+                [up, down, left, right] => Ok([up, down, left, right]),
+                _ => Err(error(4)),
+            };
+            let one = || match chars[..] {
+                [c] => Ok(c),
+                _ => Err(format!("glyphs.{}: expected 1 character", key)),
+            };
+            let border = || match chars[..] {
+                [c] => Ok(Border::solid(c)),
+                [horizontal, vertical, a, b, c, d] => Ok(Border { horizontal, vertical, corners: [a, b, c, d] }),
+                _ => Err(error(6)),
+            };
+            match key.as_str() {
+                "head" => theme.head = four()?,
+                "body" => theme.body = four()?,
+                "tail" => theme.tail = four()?,
+                "corners" => theme.corners = Some(four()?),
+                "food" => theme.food = one()?,
+                "wall" => theme.wall = one()?,
+                "rock" => theme.rock = one()?,
+                "border" => theme.border = border()?,
+                "walls" => theme.walls = border()?,
+                _ => return Err(format!("unknown glyph {:?}", key)),
+            }
+        }
+        for (key, value) in section(&mut table, "colors")? {
+            let style = |value: &Value| {
+                value.as_str()
+                    .and_then(parse_style)
+                    .ok_or_else(|| format!("colors.{}: invalid style {}", key, value))
+            };
+            let colors = &mut theme.colors;
+            match key.as_str() {
+                "head" => colors.head = style(&value)?,
+                "body" => colors.body = match value {
+                    Value::Array(ref styles) if !styles.is_empty() => {
+                        styles.iter().map(style).collect::<Result<_, _>>()?
+                    },
+                    _ => vec![style(&value)?],
+                },
+                "tail" => colors.tail = style(&value)?,
+                "food" => colors.food = style(&value)?,
+                "wall" => colors.wall = style(&value)?,
+                "rock" => colors.rock = style(&value)?,
+                "border" => colors.border = style(&value)?,
+                "banner" => colors.banner = style(&value)?,
+                _ => return Err(format!("unknown color {:?}", key)),
+            }
+        }
+        if let Some(key) = table.keys().next() {
+            return Err(format!("unknown setting {:?}", key));
+        }
+
+        // field glyphs can take two columns only in wide themes, and borders never can.
+        let glyphs = [
+            ("head", theme.head.to_vec(), theme.wide),
+            ("body", theme.body.to_vec(), theme.wide),
+            ("tail", theme.tail.to_vec(), theme.wide),
+            ("corners", theme.corners.map_or(Vec::new(), |corners| corners.to_vec()), theme.wide),
+            ("food", vec![theme.food], theme.wide),
+            ("wall", vec![theme.wall], theme.wide),
+            ("rock", vec![theme.rock], theme.wide),
+            ("border", border_chars(&theme.border), false),
+            ("walls", border_chars(&theme.walls), false),
+        ];
+        for (key, chars, wide) in glyphs {
+            if let Some(c) = chars.into_iter().find(|&c| !wide && is_wide(c)) {
+                return Err(format!("glyphs.{}: {:?} is two columns wide", key, c));
+            }
+        }
+        Ok(theme)
+    }
+
+    /// glyph of a part of the snake.
+    pub fn segment(&self, segment: Segment) -> char {
+        let by_direction = |glyphs: &[char; 4], direction| glyphs[match direction {
+            Up => 0,
+            Down => 1,
+            Left => 2,
+            Right => 3,
+        }];
+        match segment {
+            Segment::Head(dir) => by_direction(&self.head, dir),
+            Segment::Straight(dir) => by_direction(&self.body, dir),
+            Segment::Tail(dir) => by_direction(&self.tail, dir),
+            Segment::Corner { to, .. } => match self.corners {
+                None => by_direction(&self.body, to),
+                Some(corners) => {
+                    // corner by the sides of the cell it connects.
+                    let sides = segment.sides();
+                    let has = |side| sides.0 == Some(side) || sides.1 == Some(side);
+                    corners[match (has(Left), has(Up)) {
+                        (false, false) => 0,
+                        (true, false) => 1,
+                        (false, true) => 2,
+                        (true, true) => 3,
+                    }]
+                },
+            },
+        }
+    }
+}
+
+/// table of given name, empty if there is none.
+fn section(table: &mut Table, name: &str) -> Result<Table, String> {
+    match table.remove(name) {
+        None => Ok(Table::new()),
+        Some(Value::Table(section)) => Ok(section),
+        Some(_) => Err(format!("{} must be a table", name)),
+    }
+}
+
+/// every character of a border.
+fn border_chars(border: &Border) -> Vec<char> {
+    let mut chars = vec![border.horizontal, border.vertical];
+    chars.extend_from_slice(&border.corners);
+    chars
+}
+
+/// whether a character takes two columns in a terminal, as emoji and CJK characters do.
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f680..=0x1f6ff
+        | 0x1f7e0..=0x1f7eb
+        | 0x1f900..=0x1f9ff
+        | 0x1fa70..=0x1faff
+        | 0x20000..=0x3fffd)
+}
+
+/// style from words such as `yellow on blue bold`: a foreground color, `on` and a background
+/// color, `bold` and `reverse`, in any order.
+fn parse_style(text: &str) -> Option<Style> {
+    let color = |name: &str| Some(match name {
+        "default" => Color::Default,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        _ => return None,
+    });
+    let mut style = Style::PLAIN;
+    let mut words = text.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "bold" => style.bold = true,
+            "reverse" => style.reverse = true,
+            "on" => style.bg = color(words.next()?)?,
+            word => style.fg = color(word)?,
+        }
+    }
+    Some(style)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builtin_themes() {
+        // each built-in theme is what a file based on it without changes gives.
+        for theme in Theme::builtin() {
+            let text = format!("base = {:?}", theme.name);
+            assert_eq!(Theme::parse(&theme.name, &text, &Theme::builtin()), Ok(theme));
+        }
+    }
+
+    #[test]
+    fn inherit_from_base() {
+        let text = "base = \"ascii\"\n\
+                    [glyphs]\nfood = \"$\"\nhead = \"^v<>\"\n\
+                    [colors]\nbody = [\"green\", \"yellow on blue bold\"]\n";
+        let theme = Theme::parse("mine", text, &Theme::builtin()).unwrap();
+        let ascii = Theme::ascii();
+        assert_eq!(theme.name, "mine");
+        assert_eq!(theme.food, '$');
+        assert_eq!(theme.head, ['^', 'v', '<', '>']);
+        assert_eq!(theme.colors.body, [Style::fg(Color::Green), Style { bg: Color::Blue, ..Style::fg(Color::Yellow).bold() }]);
+        assert_eq!((theme.wall, theme.border, theme.colors.head), (ascii.wall, ascii.border, ascii.colors.head));
+
+        // unicode unless given.
+        assert_eq!(Theme::parse("plain", "", &[]).map(|theme| theme.body), Ok(Theme::unicode().body));
+        assert_eq!(Theme::parse("mine", "base = \"fancy\"", &Theme::builtin()).unwrap_err(), "unknown base theme \"fancy\"");
+    }
+
+    #[test]
+    fn invalid_styles() {
+        let parse = |text| Theme::parse("mine", text, &[]).unwrap_err();
+        assert_eq!(parse("[colors]\nhead = \"purple\""), "colors.head: invalid style \"purple\"");
+        assert_eq!(parse("[colors]\nfood = \"red on\""), "colors.food: invalid style \"red on\"");
+        assert_eq!(parse("[colors]\nbody = [\"green\", 3]"), "colors.body: invalid style 3");
+        assert_eq!(parse("[colors]\nsnake = \"red\""), "unknown color \"snake\"");
+    }
+
+    #[test]
+    fn invalid_glyphs() {
+        let parse = |text| Theme::parse("mine", text, &Theme::builtin()).unwrap_err();
+        assert_eq!(parse("[glyphs]\nfood = \"$$\""), "glyphs.food: expected 1 character");
+        assert_eq!(parse("[glyphs]\nfood = \"\""), "glyphs.food: expected 1 character");
+        assert_eq!(parse("[glyphs]\nhead = \"^v\""), "glyphs.head: expected 1 or 4 characters");
+        assert_eq!(parse("[glyphs]\nfood = \"🍎\""), "glyphs.food: '🍎' is two columns wide");
+        assert_eq!(parse("base = \"emoji\"\nwide = false"), "glyphs.head: '🐍' is two columns wide");
+        assert_eq!(parse("wide = true\n[glyphs]\nborder = \"国\""), "glyphs.border: '国' is two columns wide");
+        assert!(Theme::parse("mine", "wide = true\n[glyphs]\nfood = \"🍎\"", &[]).is_ok());
+    }
+}